@@ -0,0 +1,103 @@
+use std::fs;
+
+use bevy::{ asset::io::file::FileAssetReader, prelude::* };
+use serde::{ Deserialize, Serialize };
+
+use crate::save;
+use crate::{ apply_mode, reset_timers, GameMode, GameOver, GameState, Id, LoseReason, Lost, RecipeFilter, Score, SpawnParams };
+
+const FILE: &str = "campaign.ron";
+
+pub struct CampaignPlugin;
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Levels::load())
+            .insert_resource(CampaignProgress::load())
+            .init_resource::<CurrentLevel>()
+            .add_systems(OnEnter(GameState::Game), apply_level.after(apply_mode).before(reset_timers).run_if(resource_equals(GameMode::Campaign)))
+            .add_systems(Update, level_cleared.run_if(in_state(GameState::Game)).run_if(resource_equals(GameMode::Campaign)))
+            .add_systems(OnExit(GameState::Game), record_progress.run_if(resource_equals(GameMode::Campaign)));
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Level {
+    pub name: String,
+    pub recipes: Vec<String>,
+    pub target: usize,
+    pub spawn: SpawnParams,
+}
+
+// levels come from assets/levels/*.ron, played in file name order
+#[derive(Resource, Default)]
+pub struct Levels(pub Vec<Level>);
+
+impl Levels {
+    fn load() -> Self {
+        let dir = FileAssetReader::get_base_path().join("assets").join("levels");
+        let Ok(entries) = fs::read_dir(&dir) else {
+            error!("no levels found in {:?}", dir);
+            return Self::default();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        Self(paths.iter().filter_map(|path| {
+            let text = fs::read_to_string(path).ok()?;
+            ron::from_str(&text).map_err(|e| error!("broken level {:?}: {}", path, e)).ok()
+        }).collect())
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CurrentLevel(pub usize);
+
+#[derive(Resource, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    pub unlocked: usize,
+    pub best: Vec<usize>,
+}
+
+impl Default for CampaignProgress {
+    fn default() -> Self {
+        Self { unlocked: 1, best: Vec::new() }
+    }
+}
+
+impl CampaignProgress {
+    fn load() -> Self {
+        save::load(FILE)
+    }
+
+    pub fn best(&self, level: usize) -> Option<usize> {
+        self.best.get(level).copied().filter(|&best| best > 0)
+    }
+}
+
+fn apply_level(levels: Res<Levels>, current: Res<CurrentLevel>, mut filter: ResMut<RecipeFilter>, mut spawn: ResMut<SpawnParams>) {
+    let Some(level) = levels.0.get(current.0) else { return };
+    filter.0 = level.recipes.iter().map(|r| Id(r.clone())).collect();
+    *spawn = level.spawn;
+}
+
+fn level_cleared(levels: Res<Levels>, current: Res<CurrentLevel>, score: Res<Score>, mut over: EventWriter<GameOver>) {
+    let Some(level) = levels.0.get(current.0) else { return };
+    if score.0 >= level.target {
+        over.send(GameOver(LoseReason::LevelCleared));
+    }
+}
+
+fn record_progress(mut progress: ResMut<CampaignProgress>, current: Res<CurrentLevel>, levels: Res<Levels>, score: Res<Score>, lost: Res<Lost>) {
+    if progress.best.len() <= current.0 {
+        progress.best.resize(current.0 + 1, 0);
+    }
+    progress.best[current.0] = progress.best[current.0].max(score.0);
+    if let Some(LoseReason::LevelCleared) = lost.0 {
+        progress.unlocked = progress.unlocked.max(current.0 + 2).min(levels.0.len());
+    }
+    save::save(FILE, &*progress);
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use bevy::{ input::{ keyboard::KeyboardInput, ButtonState }, prelude::* };
+
+use crate::{ GameMode, GameState, InRun, Player, Process, RunState, VIEW };
+
+const RETICLE_SPEED: f32 = 400.;
+const RETICLE_SIZE: f32 = 24.;
+const RETICLE_COLOR: Color = Color::CYAN;
+const MOVE_KEYS: [(KeyCode, Vec2); 4] = [
+    (KeyCode::I, Vec2::Y),
+    (KeyCode::K, Vec2::NEG_Y),
+    (KeyCode::J, Vec2::NEG_X),
+    (KeyCode::L, Vec2::X),
+];
+
+pub struct CoopPlugin;
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::Game), spawn_reticle.run_if(two_players))
+            .add_systems(Update, (move_reticle, reticle_press).chain().run_if(in_state(GameState::Game)).run_if(in_state(RunState::Running)).run_if(two_players));
+    }
+}
+
+pub fn two_players(mode: Res<GameMode>) -> bool {
+    mode.players() == 2
+}
+
+// player two has no mouse, they steer a reticle and press tools on a second key cluster or a gamepad
+#[derive(Resource, Default)]
+pub struct PlayerTwoControls {
+    pub keys: HashMap<KeyCode, Entity>,
+    pub buttons: HashMap<GamepadButtonType, Entity>,
+}
+
+#[derive(Component)]
+struct Reticle;
+
+fn spawn_reticle(mut cmd: Commands) {
+    let bar = |size: Vec2| SpriteBundle {
+        sprite: Sprite { color: RETICLE_COLOR, custom_size: Some(size), ..default() },
+        ..default()
+    };
+    cmd.spawn((Reticle, InRun, SpatialBundle::from_transform(Transform::from_xyz(0., 0., 20.))))
+        .with_children(|parent| {
+            parent.spawn(bar(Vec2::new(RETICLE_SIZE, 3.)));
+            parent.spawn(bar(Vec2::new(3., RETICLE_SIZE)));
+        });
+}
+
+fn move_reticle(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    mut reticle: Query<&mut Transform, With<Reticle>>,
+) {
+    let Ok(mut transform) = reticle.get_single_mut() else { return };
+
+    let mut dir: Vec2 = MOVE_KEYS.iter().filter(|(key, _)| keys.pressed(*key)).map(|(_, d)| *d).sum();
+    for pad in gamepads.iter() {
+        let x = axes.get(GamepadAxis::new(pad, GamepadAxisType::LeftStickX)).unwrap_or(0.);
+        let y = axes.get(GamepadAxis::new(pad, GamepadAxisType::LeftStickY)).unwrap_or(0.);
+        dir += Vec2::new(x, y);
+    }
+
+    let pos = transform.translation.xy() + dir.clamp_length_max(1.) * RETICLE_SPEED * time.delta_seconds();
+    let half = VIEW / 2.;
+    transform.translation = pos.clamp(-half, half).extend(transform.translation.z);
+}
+
+fn reticle_press(
+    mut keyevt: EventReader<KeyboardInput>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    controls: Res<PlayerTwoControls>,
+    reticle: Query<&Transform, With<Reticle>>,
+    mut process: EventWriter<Process>,
+) {
+    let Ok(transform) = reticle.get_single() else { return };
+    let pos = transform.translation.xy();
+
+    for ev in keyevt.read() {
+        if ev.state != ButtonState::Pressed { continue };
+        let Some(tool) = ev.key_code.and_then(|key| controls.keys.get(&key)) else { continue };
+        process.send(Process(*tool, pos, Player::Two));
+    }
+    for pad in gamepads.iter() {
+        for (button, tool) in controls.buttons.iter() {
+            if buttons.just_pressed(GamepadButton::new(pad, *button)) {
+                process.send(Process(*tool, pos, Player::Two));
+            }
+        }
+    }
+}
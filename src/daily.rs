@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use chrono::Datelike;
+use rand::{ rngs::StdRng, seq::{ IteratorRandom, SliceRandom }, SeedableRng };
+use serde::{ Deserialize, Serialize };
+
+use crate::save;
+use crate::{ apply_mode, reset_run, reset_timers, Active, Food, GameMode, GameRng, GameState, Id, LoseCondition, RecipeFilter, Score, ScoreBreakdown, SpawnParams };
+
+const FILE: &str = "daily.ron";
+const MUTATORS_PER_DAY: usize = 2;
+
+pub struct DailyPlugin;
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(DailyResults::load())
+            .init_resource::<DailyDate>()
+            .add_systems(OnEnter(GameState::Game), start_daily.after(reset_run).after(apply_mode).before(reset_timers).run_if(resource_equals(GameMode::Daily)))
+            .add_systems(OnExit(GameState::Game), finish_daily.run_if(resource_equals(GameMode::Daily)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mutator {
+    Rush,
+    Butterfingers,
+    Steep,
+    Fragile,
+    Crowded,
+    SmallMenu,
+}
+
+const MUTATORS: [Mutator; 6] = [Mutator::Rush, Mutator::Butterfingers, Mutator::Steep, Mutator::Fragile, Mutator::Crowded, Mutator::SmallMenu];
+
+impl Mutator {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mutator::Rush => "rush",
+            Mutator::Butterfingers => "butterfingers",
+            Mutator::Steep => "steep",
+            Mutator::Fragile => "fragile",
+            Mutator::Crowded => "crowded",
+            Mutator::SmallMenu => "small menu",
+        }
+    }
+}
+
+// everything about today's challenge follows from the local date
+pub struct Challenge {
+    pub date: String,
+    pub seed: u64,
+    pub mutators: Vec<Mutator>,
+}
+
+impl Challenge {
+    pub fn today() -> Self {
+        let today = chrono::Local::now().date_naive();
+        let seed = today.year() as u64 * 10000 + today.month() as u64 * 100 + today.day() as u64;
+        let mut rng = StdRng::seed_from_u64(seed);
+        Self {
+            date: today.format("%Y-%m-%d").to_string(),
+            seed,
+            mutators: MUTATORS.into_iter().choose_multiple(&mut rng, MUTATORS_PER_DAY),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        self.mutators.iter().map(|m| m.name()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyResult {
+    pub date: String,
+    pub score: usize,
+    pub served: usize,
+    pub mutators: Vec<String>,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct DailyResults(pub Vec<DailyResult>);
+
+impl DailyResults {
+    fn load() -> Self {
+        save::load(FILE)
+    }
+
+    pub fn get(&self, date: &str) -> Option<&DailyResult> {
+        self.0.iter().find(|r| r.date == date)
+    }
+
+    fn get_mut(&mut self, date: &str) -> Option<&mut DailyResult> {
+        self.0.iter_mut().find(|r| r.date == date)
+    }
+}
+
+// the date the running challenge started on, a run going past midnight still scores for that day
+#[derive(Resource, Default)]
+struct DailyDate(String);
+
+fn start_daily(
+    mut rng: ResMut<GameRng>,
+    mut spawn: ResMut<SpawnParams>,
+    mut cond: ResMut<LoseCondition>,
+    mut filter: ResMut<RecipeFilter>,
+    mut results: ResMut<DailyResults>,
+    mut date: ResMut<DailyDate>,
+    recipes: Query<&Id, (With<Food>, Without<Active>)>,
+) {
+    let challenge = Challenge::today();
+    *rng = GameRng::new(challenge.seed);
+    date.0 = challenge.date.clone();
+
+    for mutator in challenge.mutators.iter() {
+        match mutator {
+            Mutator::Rush => spawn.food *= 0.5,
+            Mutator::Butterfingers => spawn.throw += 0.4,
+            Mutator::Steep => spawn.ramp *= 2.,
+            Mutator::Fragile => cond.strikes = Some(2),
+            Mutator::Crowded => cond.max_queue = Some(4),
+            Mutator::SmallMenu => {
+                let mut picker = StdRng::seed_from_u64(challenge.seed);
+                let mut pool: Vec<_> = recipes.iter().cloned().collect();
+                pool.sort_by_key(|id| id.0.clone());
+                filter.0 = pool.choose_multiple(&mut picker, 3).cloned().collect();
+            }
+        }
+    }
+
+    // the attempt counts as soon as it starts, closing the game doesn't give a retry
+    if results.get(&challenge.date).is_none() {
+        results.0.push(DailyResult {
+            date: challenge.date,
+            score: 0,
+            served: 0,
+            mutators: challenge.mutators.iter().map(|m| m.name().to_string()).collect(),
+        });
+        save::save(FILE, &*results);
+    }
+}
+
+fn finish_daily(mut results: ResMut<DailyResults>, date: Res<DailyDate>, score: Res<Score>, breakdown: Res<ScoreBreakdown>) {
+    let Some(result) = results.get_mut(&date.0) else { return };
+    result.score = score.0;
+    result.served = breakdown.served;
+    save::save(FILE, &*results);
+}
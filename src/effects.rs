@@ -0,0 +1,198 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{ Active, Food, GameState, Grade, Id, InRun, OrderCompleted, Outcome, Processed, RunState, Scored, Tex };
+
+const POPUP_TIME: f32 = 0.8;
+const POPUP_RISE: f32 = 40.;
+const DISH_FLIGHT: f32 = 0.6;
+// how high the dish arcs on its way to the queue
+const DISH_ARC: f32 = 60.;
+
+pub struct EffectsPlugin;
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            hit_feedback,
+            score_popups,
+            send_dish,
+            move_particles,
+            move_popups,
+            fly_dishes)
+            .run_if(in_state(GameState::Game))
+            .run_if(in_state(RunState::Running)));
+    }
+}
+
+#[derive(Component)]
+struct Particle {
+    v: Vec2,
+    g: f32,
+    grow: f32,
+    life: Timer
+}
+
+#[derive(Component)]
+struct Popup(Timer);
+
+#[derive(Component)]
+struct FlyingDish {
+    from: Vec2,
+    to: Vec2,
+    t: Timer
+}
+
+enum Burst {
+    Sparks,
+    Slices,
+    Steam,
+    Crumbs,
+    Puff
+}
+
+impl Burst {
+    fn for_tool(tool: &str) -> Self {
+        match tool {
+            "pan" => Burst::Sparks,
+            "knife" => Burst::Slices,
+            "pot" => Burst::Steam,
+            _ => Burst::Crumbs
+        }
+    }
+
+    fn spawn(&self, cmd: &mut Commands, at: Vec2) {
+        // cosmetic only, so it stays off the seeded game rng
+        let mut rng = rand::thread_rng();
+        let (count, color, size) = match self {
+            Burst::Sparks => (10, Color::ORANGE, Vec2::splat(4.)),
+            Burst::Slices => (6, Color::rgb(0.9, 1., 0.8), Vec2::new(14., 3.)),
+            Burst::Steam => (6, Color::rgba(1., 1., 1., 0.5), Vec2::splat(12.)),
+            Burst::Crumbs => (8, Color::rgb(0.6, 0.4, 0.2), Vec2::splat(3.)),
+            Burst::Puff => (8, Color::rgba(0.5, 0.5, 0.5, 0.6), Vec2::splat(10.))
+        };
+        for _ in 0..count {
+            let angle = rng.gen_range(0. .. 2. * PI);
+            let dir = Vec2::new(angle.cos(), angle.sin());
+            let (v, g, grow, life) = match self {
+                Burst::Sparks => (dir * rng.gen_range(150. .. 300.) + Vec2::Y * 100., 600., 0., 0.4),
+                Burst::Slices => (Vec2::new(dir.x * rng.gen_range(100. .. 200.), rng.gen_range(50. .. 150.)), 400., 0., 0.5),
+                Burst::Steam => (Vec2::new(dir.x * 15., rng.gen_range(40. .. 80.)), 0., 1.5, 0.9),
+                Burst::Crumbs => (dir * rng.gen_range(80. .. 160.), 500., 0., 0.4),
+                Burst::Puff => (dir * rng.gen_range(20. .. 50.), 0., 1., 0.5)
+            };
+            cmd.spawn((
+                Particle { v, g, grow, life: Timer::from_seconds(life, TimerMode::Once) },
+                InRun,
+                SpriteBundle {
+                    sprite: Sprite { color, custom_size: Some(size), ..default() },
+                    transform: Transform::from_translation((at + dir * 8.).extend(25.))
+                        .with_rotation(Quat::from_rotation_z(angle)),
+                    ..default()
+                }
+            ));
+        }
+    }
+}
+
+fn popup(cmd: &mut Commands, at: Vec2, text: String, color: Color) {
+    cmd.spawn((
+        Popup(Timer::from_seconds(POPUP_TIME, TimerMode::Once)),
+        InRun,
+        Text2dBundle {
+            text: Text::from_section(text, TextStyle { font_size: 20., color, ..default() }),
+            transform: Transform::from_translation(at.extend(30.)),
+            ..default()
+        }
+    ));
+}
+
+// every hit gets its grade and a burst in the tool's style, wasted ones just go up in smoke
+fn hit_feedback(mut cmd: Commands, mut processed: EventReader<Processed>, ids: Query<&Id>) {
+    for evt in processed.read() {
+        match evt.outcome {
+            Outcome::Used(_) | Outcome::Cooking(_) => {
+                let (text, color) = match evt.grade {
+                    Grade::Perfect => ("perfect", Color::GOLD),
+                    Grade::Good => ("good", Color::LIME_GREEN),
+                    Grade::Ok => ("ok", Color::WHITE)
+                };
+                popup(&mut cmd, evt.at, text.to_string(), color);
+                let tool = ids.get(evt.process).map(|id| id.0.as_str()).unwrap_or_default();
+                Burst::for_tool(tool).spawn(&mut cmd, evt.at);
+            }
+            Outcome::Busy | Outcome::Wasted => {
+                popup(&mut cmd, evt.at, "wasted".to_string(), Color::RED);
+                Burst::Puff.spawn(&mut cmd, evt.at);
+            }
+        }
+    }
+}
+
+// points only land once the order is done, so that's when they show up
+fn score_popups(mut cmd: Commands, mut scored: EventReader<Scored>) {
+    for evt in scored.read() {
+        popup(&mut cmd, evt.at + Vec2::Y * 20., format!("+{}", evt.points), Color::GOLD);
+    }
+}
+
+// the finished dish flies back to where its order was waiting
+#[allow(clippy::type_complexity)]
+fn send_dish(mut cmd: Commands, mut completed: EventReader<OrderCompleted>, recipes: Query<(&Id, &Tex), (With<Food>, Without<Active>)>) {
+    for evt in completed.read() {
+        let Some((_, tex)) = recipes.iter().find(|(id, _)| **id == evt.recipe) else { continue };
+        cmd.spawn((
+            FlyingDish { from: evt.at, to: evt.slot, t: Timer::from_seconds(DISH_FLIGHT, TimerMode::Once) },
+            InRun,
+            SpriteBundle {
+                texture: tex.0.clone(),
+                transform: Transform::from_translation(evt.at.extend(30.)),
+                ..default()
+            }
+        ));
+    }
+}
+
+fn move_particles(mut cmd: Commands, mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>, time: Res<Time>) {
+    let dt = time.delta_seconds();
+    for (e, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        if particle.life.tick(time.delta()).finished() {
+            cmd.entity(e).despawn();
+            continue;
+        }
+        let g = particle.g;
+        particle.v.y -= g * dt;
+        transform.translation += (particle.v * dt).extend(0.);
+        transform.scale += Vec3::splat(particle.grow * dt);
+        let alpha = sprite.color.a();
+        sprite.color.set_a(alpha.min(particle.life.percent_left()));
+    }
+}
+
+fn move_popups(mut cmd: Commands, mut popups: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>, time: Res<Time>) {
+    for (e, mut popup, mut transform, mut text) in popups.iter_mut() {
+        if popup.0.tick(time.delta()).finished() {
+            cmd.entity(e).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE * time.delta_seconds();
+        let alpha = popup.0.percent_left();
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
+
+fn fly_dishes(mut cmd: Commands, mut dishes: Query<(Entity, &mut FlyingDish, &mut Transform)>, time: Res<Time>) {
+    for (e, mut dish, mut transform) in dishes.iter_mut() {
+        if dish.t.tick(time.delta()).finished() {
+            cmd.entity(e).despawn();
+            continue;
+        }
+        // ease out so it lands softly, shrinking into the queue
+        let t = dish.t.percent();
+        let eased = 1. - (1. - t).powi(2);
+        let pos = dish.from.lerp(dish.to, eased) + Vec2::Y * DISH_ARC * (PI * t).sin();
+        transform.translation = pos.extend(transform.translation.z);
+        transform.scale = Vec3::splat(1. - 0.5 * t);
+    }
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use serde::{ Deserialize, Serialize };
+
+use crate::save;
+
+const MAX_ENTRIES: usize = 10;
+const FILE: &str = "highscores.ron";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entry {
+    pub name: String,
+    pub score: usize,
+    pub date: String,
+    pub seed: u64,
+    pub mode: String,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct HighScores(pub Vec<Entry>);
+
+impl HighScores {
+    pub fn load() -> Self {
+        let mut scores: HighScores = save::load(FILE);
+        scores.0.sort_by_key(|e| std::cmp::Reverse(e.score));
+        scores.0.truncate(MAX_ENTRIES);
+        scores
+    }
+
+    pub fn save(&self) {
+        save::save(FILE, self);
+    }
+
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0 && (self.0.len() < MAX_ENTRIES || self.0.iter().any(|e| score > e.score))
+    }
+
+    pub fn insert(&mut self, entry: Entry) {
+        let i = self.0.iter().position(|e| entry.score > e.score).unwrap_or(self.0.len());
+        self.0.insert(i, entry);
+        self.0.truncate(MAX_ENTRIES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: usize) -> Entry {
+        Entry { name: name.to_string(), score, date: String::new(), seed: 0, mode: String::new() }
+    }
+
+    fn full() -> HighScores {
+        HighScores((1..=MAX_ENTRIES).rev().map(|i| entry(&i.to_string(), i * 10)).collect())
+    }
+
+    #[test]
+    fn insert_keeps_the_table_sorted_and_capped() {
+        let mut scores = full();
+        scores.insert(entry("new", 55));
+        assert_eq!(scores.0.len(), MAX_ENTRIES);
+        let order: Vec<_> = scores.0.iter().map(|e| e.score).collect();
+        assert_eq!(order, [100, 90, 80, 70, 60, 55, 50, 40, 30, 20]);
+    }
+
+    #[test]
+    fn ties_go_after_existing_entries() {
+        let mut scores = full();
+        scores.insert(entry("new", 50));
+        let at = scores.0.iter().position(|e| e.name == "new").unwrap();
+        assert_eq!(scores.0[at - 1].name, "5");
+        assert_eq!(scores.0[at - 1].score, 50);
+    }
+
+    #[test]
+    fn qualifies_only_when_it_beats_the_table() {
+        let scores = full();
+        assert!(!scores.qualifies(0));
+        assert!(!scores.qualifies(10));
+        assert!(scores.qualifies(11));
+        assert!(HighScores::default().qualifies(1));
+    }
+}
@@ -1,22 +1,322 @@
 mod ui;
-use ui::MenuPlugin;
-use std::{collections::HashMap, time::Duration};
+mod highscore;
+mod runlog;
+mod save;
+mod campaign;
+mod daily;
+mod tutorial;
+mod coop;
+mod versus;
+mod settings;
+mod effects;
+mod view;
+use ui::{ MenuPlugin, HudPlugin };
+use highscore::HighScores;
+use runlog::RunLogPlugin;
+use campaign::CampaignPlugin;
+use daily::DailyPlugin;
+use tutorial::TutorialPlugin;
+use coop::{ CoopPlugin, PlayerTwoControls };
+use versus::VersusPlugin;
+use effects::EffectsPlugin;
+use view::ViewPlugin;
+use settings::Settings;
+use std::{collections::{HashMap, HashSet}, time::Duration};
 
 use bevy::input::keyboard::KeyboardInput;
 use bevy::input::ButtonState;
-use bevy::window::WindowResolution;
-use rand::seq::{ SliceRandom, IteratorRandom };
+use bevy::window::WindowFocused;
+use bevy::sprite::Anchor;
+use rand::seq::SliceRandom;
 
 use bevy::{prelude::*, time::Stopwatch};
-use rand::Rng;
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use serde::Deserialize;
 
+// the play area in world units, the camera scales it to fit whatever the window is
+const VIEW: Vec2 = Vec2::new(720., 480.);
 const HITBOX_RAD: f32 = 50.;
 const FOOD_SPAWN: f32 = 10.;
 const INGREDIENT_SPAWN: f32 = 3.;
+const PATIENCE_BAR_WIDTH: f32 = 56.;
+const STEP_ICON: f32 = 12.;
+const STEP_COLUMNS: usize = 3;
+const ORDER_SPACING: f32 = 75.;
+const POINTS_PER_STEP: usize = 10;
+const SHIFT_LENGTH: f32 = 180.;
+// how many orders can wait before the run is lost, per player when the queues are split
+const MAX_QUEUE: usize = 7;
+const THROW_SEED: u64 = 0x7468726f77;
+const PRESS_FLASH: f32 = 0.15;
+const MISS_FLASH: f32 = 0.35;
+// how much bigger a tool pops on a press, and how far it wobbles on a miss in radians
+const TOOL_POP: f32 = 0.25;
+const TOOL_SHAKE: f32 = 0.3;
+const TARGET_TOOL: f32 = 20.;
 
 #[derive(Resource, Default)]
 struct Score(usize);
 
+#[derive(Resource, Default)]
+struct FailedOrders(usize);
+
+#[derive(Resource, Default)]
+struct Wasted(usize);
+
+// recipes that may show up this run, empty means all of them
+#[derive(Resource, Default)]
+struct RecipeFilter(HashSet<Id>);
+
+impl RecipeFilter {
+    fn allows(&self, id: &Id) -> bool {
+        self.0.is_empty() || self.0.contains(id)
+    }
+}
+
+#[derive(Resource, Default)]
+struct ZenRecipes(HashSet<Id>);
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+enum GameMode {
+    #[default]
+    Endless,
+    Shift,
+    Zen,
+    Campaign,
+    Daily,
+    Tutorial,
+    Coop,
+    Versus
+}
+
+impl GameMode {
+    fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "endless",
+            GameMode::Shift => "shift",
+            GameMode::Zen => "zen",
+            GameMode::Campaign => "campaign",
+            GameMode::Daily => "daily",
+            GameMode::Tutorial => "tutorial",
+            GameMode::Coop => "co-op",
+            GameMode::Versus => "versus"
+        }
+    }
+
+    // zen and the tutorial never speed up and can't be lost
+    fn spawn_params(self) -> SpawnParams {
+        match self {
+            GameMode::Zen | GameMode::Tutorial => SpawnParams { ramp: 0., ..default() },
+            _ => SpawnParams::default()
+        }
+    }
+
+    fn can_lose(self) -> bool {
+        !matches!(self, GameMode::Zen | GameMode::Tutorial)
+    }
+
+    fn players(self) -> usize {
+        match self {
+            GameMode::Coop | GameMode::Versus => 2,
+            _ => 1
+        }
+    }
+
+    // every player gets their own orders
+    fn split_queues(self) -> bool {
+        self == GameMode::Versus
+    }
+
+    // the tutorial puts up its own orders and ingredients
+    fn random_spawns(self) -> bool {
+        self != GameMode::Tutorial
+    }
+
+    fn lose_condition(self) -> LoseCondition {
+        match self {
+            // a shift only ends when the bell rings
+            GameMode::Shift => LoseCondition { max_queue: None, strikes: None, time_limit: Some(SHIFT_LENGTH), drop_strikes: false },
+            GameMode::Zen | GameMode::Tutorial => LoseCondition { max_queue: None, strikes: None, time_limit: None, drop_strikes: false },
+            // half the screen each, whoever gets snowed under first loses
+            GameMode::Versus => LoseCondition { max_queue: Some(MAX_QUEUE), strikes: None, time_limit: None, drop_strikes: false },
+            GameMode::Endless | GameMode::Campaign | GameMode::Daily | GameMode::Coop => LoseCondition { drop_strikes: true, ..default() }
+        }
+    }
+}
+
+// how fast orders and ingredients come in, and how much faster they get per served dish
+#[derive(Resource, Deserialize, Clone, Copy, Debug)]
+struct SpawnParams {
+    food: f32,
+    ingredient: f32,
+    throw: f32,
+    ramp: f32
+}
+
+impl Default for SpawnParams {
+    fn default() -> Self {
+        Self { food: FOOD_SPAWN, ingredient: INGREDIENT_SPAWN, throw: 0.1, ramp: 1. }
+    }
+}
+
+impl SpawnParams {
+    fn food_interval(&self, served: usize) -> f32 {
+        (self.food - served as f32 * 0.05 * self.ramp).max(1.)
+    }
+
+    fn ingredient_interval(&self, served: usize) -> f32 {
+        (self.ingredient - served as f32 * 0.5 * self.ramp).max(0.3)
+    }
+
+    fn throw_time(&self, served: usize) -> f32 {
+        (self.throw + served as f32 * 0.1 * self.ramp).min(5.)
+    }
+}
+
+#[derive(Resource, Default)]
+struct Combo {
+    current: usize,
+    best: usize
+}
+
+impl Combo {
+    fn hit(&mut self) {
+        self.current += 1;
+        self.best = self.best.max(self.current);
+    }
+
+    // +10% per hit in a row, capped at double points
+    fn multiplier(&self) -> f32 {
+        1. + (self.current as f32 * 0.1).min(1.)
+    }
+}
+
+// where the points came from, for the end screen
+#[derive(Resource, Default)]
+struct ScoreBreakdown {
+    served: usize,
+    base: usize,
+    grade: usize,
+    combo: usize,
+    speed: usize,
+    penalty: usize
+}
+
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    score: usize,
+    served: usize,
+    hits: usize,
+    misses: usize,
+    wasted: usize
+}
+
+// the same numbers split by who did it, only interesting with two players
+#[derive(Resource, Default)]
+struct PlayerStats([Stats; 2]);
+
+impl PlayerStats {
+    fn of(&mut self, player: Player) -> &mut Stats {
+        &mut self.0[player.index()]
+    }
+}
+
+#[derive(Resource, Default)]
+struct RecipesServed(HashMap<Id, usize>);
+
+// set to replay the last run's seed instead of rolling a new one
+#[derive(Resource, Default)]
+struct RetrySeed(Option<u64>);
+
+#[derive(Resource, Default)]
+struct Strikes(usize);
+
+#[derive(Resource, Default)]
+struct RunTime(Stopwatch);
+
+#[derive(Clone, Copy, Debug)]
+enum StrikeReason {
+    WrongTool,
+    Expired,
+    Dropped
+}
+
+#[derive(Clone, Copy, Debug)]
+enum LoseReason {
+    QueueFull,
+    OutOfStrikes,
+    TimeUp,
+    OrderExpired,
+    LevelCleared,
+    Overrun(Player)
+}
+
+impl LoseReason {
+    fn describe(self) -> &'static str {
+        match self {
+            LoseReason::QueueFull => "too many orders piled up",
+            LoseReason::OutOfStrikes => "out of lives",
+            LoseReason::TimeUp => "time is up",
+            LoseReason::OrderExpired => "a customer left hungry",
+            LoseReason::LevelCleared => "level cleared!",
+            LoseReason::Overrun(Player::One) => "player two wins, player one got snowed under",
+            LoseReason::Overrun(Player::Two) => "player one wins, player two got snowed under"
+        }
+    }
+}
+
+// every limit is optional, the run ends as soon as any of them is hit.
+// there's no outside config for this, each mode sets its own on entering the game
+#[derive(Resource)]
+struct LoseCondition {
+    max_queue: Option<usize>,
+    strikes: Option<usize>,
+    time_limit: Option<f32>,
+    // letting the last copy of a needed ingredient fall costs a life
+    drop_strikes: bool
+}
+
+impl Default for LoseCondition {
+    fn default() -> Self {
+        Self { max_queue: Some(MAX_QUEUE), strikes: Some(5), time_limit: None, drop_strikes: false }
+    }
+}
+
+impl LoseCondition {
+    fn lives(&self, strikes: &Strikes) -> Option<usize> {
+        self.strikes.map(|max| max.saturating_sub(strikes.0))
+    }
+
+    fn time_left(&self, time: &RunTime) -> Option<f32> {
+        self.time_limit.map(|limit| (limit - time.0.elapsed_secs()).max(0.))
+    }
+}
+
+#[derive(Resource, Default)]
+struct Lost(Option<LoseReason>);
+
+// everything random in a run comes out of here so a seed reproduces it
+// orders and throws draw from separate streams, the throws depend on what the player has on the board
+// so sharing one stream would make the order queue drift apart between two runs on the same seed
+#[derive(Resource)]
+struct GameRng {
+    seed: u64,
+    orders: StdRng,
+    throws: StdRng
+}
+
+impl GameRng {
+    fn new(seed: u64) -> Self {
+        Self { seed, orders: StdRng::seed_from_u64(seed), throws: StdRng::seed_from_u64(seed ^ THROW_SEED) }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
 #[derive(Component)]
 struct Ingredient;
 
@@ -35,10 +335,7 @@ struct Tex(Handle<Image>);
 #[derive(Resource, Default)]
 struct Ingredients(HashMap<Id, Entity>);
 
-#[derive(Resource, Default)]
-struct FoodsCount(u32);
-
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 struct IngredientProcessing {
     ingredient: Id,
     processing: Id
@@ -65,18 +362,203 @@ struct IngredientBundle {
     marker: Ingredient
 }
 
+#[derive(Component)]
+struct Station {
+    cook: Duration,
+    burn: Duration,
+    slot: Option<Cooking>
+}
+
+struct Cooking {
+    food: Entity,
+    step: IngredientProcessing,
+    grade: Grade,
+    t: Stopwatch
+}
+
+impl Station {
+    fn new(cook: f32, burn: f32) -> Self {
+        Self { cook: Duration::from_secs_f32(cook), burn: Duration::from_secs_f32(burn), slot: None }
+    }
+
+    fn done(&self) -> bool {
+        self.slot.as_ref().is_some_and(|c| c.t.elapsed() >= self.cook)
+    }
+
+    fn burnt(&self) -> bool {
+        self.slot.as_ref().is_some_and(|c| c.t.elapsed() >= self.cook + self.burn)
+    }
+
+    fn cooking(&self, food: Entity) -> bool {
+        self.slot.as_ref().is_some_and(|c| c.food == food)
+    }
+}
+
+#[derive(Component)]
+struct Slot;
+
+// one ingredient+tool pair in the little grid under an order
+#[derive(Component)]
+struct StepIcon(IngredientProcessing);
+
+#[derive(Component)]
+struct StepCheck;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Grade {
+    Ok,
+    Good,
+    Perfect
+}
+
+impl Grade {
+    // both inputs go from 0 (dead on) to 1 (barely made it)
+    fn new(arc: f32, center: f32) -> Self {
+        match (arc + center) / 2. {
+            q if q < 0.25 => Grade::Perfect,
+            q if q < 0.5 => Grade::Good,
+            _ => Grade::Ok
+        }
+    }
+
+    fn multiplier(self) -> f32 {
+        match self {
+            Grade::Perfect => 3.,
+            Grade::Good => 2.,
+            Grade::Ok => 1.
+        }
+    }
+}
+
+#[derive(Component, Default)]
+struct Grades(Vec<Grade>);
+
+#[derive(Component)]
+struct Patience(Timer);
+
+// the nth order put up this run, entity ids get recycled so they can't tell which came first
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SpawnIndex(usize);
+
+#[derive(Resource, Default)]
+struct OrderCount(usize);
+
+#[derive(Component)]
+struct PatienceBar;
+
+#[derive(Reflect, Clone, Copy)]
+enum Expiry {
+    Nothing,
+    Penalty(usize),
+    GameOver
+}
+
+#[derive(Reflect, Resource)]
+#[reflect(Resource)]
+struct PatienceConfig {
+    base: f32,
+    per_step: f32,
+    expiry: Expiry
+}
+
+impl Default for PatienceConfig {
+    fn default() -> Self {
+        Self { base: 20., per_step: 8., expiry: Expiry::Penalty(POINTS_PER_STEP) }
+    }
+}
+
 #[derive(Resource)]
 struct KeyMapping(HashMap<KeyCode, Entity>);
 
+// keys bound to a tool as printed on screen, Key1 shows up as 1
+fn key_label(keymap: &KeyMapping, tool: Entity) -> Option<String> {
+    let mut keys: Vec<_> = keymap.0.iter()
+        .filter(|(_, &e)| e == tool)
+        .map(|(key, _)| format!("{:?}", key).trim_start_matches("Key").to_string())
+        .collect();
+    keys.sort();
+    (!keys.is_empty()).then(|| keys.join("/"))
+}
+
+#[derive(Component)]
+struct KeyLabel;
+
+// marks what a press would hit right now
+#[derive(Component)]
+struct Target;
+
+#[derive(Component)]
+struct TargetBackdrop;
+
+// one per tool, shown when an order wants the target done with it
+#[derive(Component)]
+struct TargetTool(Entity);
+
+#[derive(Component)]
+struct ToolFlash {
+    press: Timer,
+    miss: Timer
+}
+
+impl Default for ToolFlash {
+    fn default() -> Self {
+        // start out finished so nothing plays until the first press
+        let done = |secs| {
+            let mut t = Timer::from_seconds(secs, TimerMode::Once);
+            t.tick(t.duration());
+            t
+        };
+        Self { press: done(PRESS_FLASH), miss: done(MISS_FLASH) }
+    }
+}
+
 #[derive(Bundle)]
 struct ProcessingBundle {
     id: Id,
     tex: Tex,
+    flash: ToolFlash,
     marker: Processing
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+enum Player {
+    #[default]
+    One,
+    Two
+}
+
+impl Player {
+    fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1
+        }
+    }
+}
+
+// whose queue an order sits in when the players don't share one
+#[derive(Component, Clone, Copy)]
+struct Owner(Player);
+
+#[derive(Event)]
+struct Process(Entity, Vec2, Player);
+
+#[derive(Event)]
+struct Serve(Entity, Player);
+
 #[derive(Event)]
-struct Process(Entity, Vec2);
+struct Miss(Entity, Vec2, Player);
+
+// the station something was left on for too long
+#[derive(Event)]
+struct Burnt(Entity);
+
+// what a finished order actually added to the score and where it was finished
+#[derive(Event)]
+struct Scored {
+    points: usize,
+    at: Vec2
+}
 
 #[derive(Reflect, Resource, Default)]
 #[reflect(Resource)]
@@ -95,6 +577,10 @@ struct FoodSpawnTimer(Timer);
 #[derive(Resource)]
 struct DespawnTimer(Timer);
 
+// everything spawned for a single run, it all goes when the run ends
+#[derive(Component)]
+struct InRun;
+
 #[derive(Component)]
 struct Throw {
     t: Stopwatch,
@@ -110,6 +596,18 @@ enum GameState {
     #[default]
     MainMenu,
     Game,
+    NameEntry,
+    GameOver,
+    // passes straight through to Game so a restart runs the exit and enter schedules
+    Restart,
+}
+
+// only means something while in GameState::Game
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+enum RunState {
+    #[default]
+    Running,
+    Paused,
 }
 
 fn main() {
@@ -119,37 +617,107 @@ fn main() {
             .set(WindowPlugin {
                 primary_window: Some(Window {
                     title: "yo".into(),
-                    resolution: (720., 480.).into(),
-                    resizable: false,
+                    resolution: (VIEW.x, VIEW.y).into(),
                     ..Default::default()
                 }),
                 ..Default::default()
             }))
-        .add_plugins(MenuPlugin)
-        .insert_resource(ClearColor(Color::rgb(255. / 255., 105. / 255., 180. / 255.)))
+        .add_plugins((MenuPlugin, HudPlugin, RunLogPlugin, CampaignPlugin, DailyPlugin, TutorialPlugin, CoopPlugin, VersusPlugin, EffectsPlugin, ViewPlugin))
+        .insert_resource(ClearColor(Color::rgb(1., 105. / 255., 180. / 255.)))
         .add_state::<GameState>()
+        .add_state::<RunState>()
         .init_resource::<Ingredients>()
-        .init_resource::<FoodsCount>()
         .init_resource::<Score>()
+        .init_resource::<FailedOrders>()
+        .init_resource::<Wasted>()
+        .init_resource::<GameMode>()
+        .init_resource::<RecipeFilter>()
+        .init_resource::<SpawnParams>()
+        .init_resource::<ZenRecipes>()
+        .init_resource::<Combo>()
+        .init_resource::<ScoreBreakdown>()
+        .init_resource::<PatienceConfig>()
+        .init_resource::<Strikes>()
+        .init_resource::<PlayerStats>()
+        .init_resource::<RecipesServed>()
+        .init_resource::<OrderCount>()
+        .init_resource::<RetrySeed>()
+        .init_resource::<RunTime>()
+        .init_resource::<LoseCondition>()
+        .init_resource::<Lost>()
+        .init_resource::<GameRng>()
+        .insert_resource(HighScores::load())
+        .insert_resource(Settings::load())
         .register_type::<ThrowConfig>()
+        .register_type::<PatienceConfig>()
         .add_event::<Process>()
         .add_event::<ProcessIngredient>()
-        .add_event::<RecipeComplete>()
+        .add_event::<OrderSpawned>()
+        .add_event::<StepCompleted>()
+        .add_event::<OrderCompleted>()
+        .add_event::<OrderFailed>()
+        .add_event::<Serve>()
+        .add_event::<Miss>()
+        .add_event::<Processed>()
+        .add_event::<Strike>()
+        .add_event::<Scored>()
+        .add_event::<Burnt>()
+        .add_event::<GameOver>()
         .add_systems(Startup, setup)
-        .add_systems(OnEnter(GameState::Game), reset_score)
+        .add_systems(OnEnter(GameState::Game), (
+            reset_run,
+            apply_mode,
+            reset_timers.after(apply_mode),
+            spawn_target,
+            clear_events::<Process>,
+            clear_events::<ProcessIngredient>,
+            clear_events::<OrderSpawned>,
+            clear_events::<StepCompleted>,
+            clear_events::<OrderCompleted>,
+            clear_events::<OrderFailed>,
+            clear_events::<Serve>,
+            clear_events::<Miss>,
+            clear_events::<Processed>,
+            clear_events::<Strike>,
+            clear_events::<Scored>,
+            clear_events::<Burnt>,
+            clear_events::<GameOver>))
+        .add_systems(OnExit(GameState::Game), clear_field)
+        .add_systems(OnEnter(GameState::Restart), |mut game_state: ResMut<NextState<GameState>>| game_state.set(GameState::Game))
         .add_systems(Update, (
-            spawn_ingredients,
+            spawn_ingredients.run_if(|mode: Res<GameMode>| mode.random_spawns()),
             move_ingredients,
             despawn_ingredients,
-            spawn_foods,
+            spawn_foods.run_if(|mode: Res<GameMode>| mode.random_spawns()),
+            // nothing runs out in the modes you can't lose
+            expire_orders.run_if(|mode: Res<GameMode>| mode.can_lose()),
             draw_active_foods,
             keypress,
             process,
             process_ingredient,
+            serve,
+            cook,
             draw_processing,
+            label_keys,
+            flash_tools,
+            animate_tools,
+            draw_stations,
+            add_step_icons,
+            draw_step_icons,
+            highlight_target)
+            .run_if(in_state(GameState::Game))
+            .run_if(in_state(RunState::Running)))
+        .add_systems(Update, (
+            order_expired,
             loose,
-            count_score)
-            .run_if(in_state(GameState::Game)))
+            count_strikes,
+            count_misses,
+            count_waste,
+            game_over,
+            count_score,
+            pause)
+            .run_if(in_state(GameState::Game))
+            .run_if(in_state(RunState::Running)))
         .run();
 }
 
@@ -157,67 +725,382 @@ fn main() {
 struct ProcessIngredient {
     active: Entity,
     ingredient: Entity,
-    process: Entity
+    process: Entity,
+    grade: Grade,
+    // where the ingredient was when it got hit
+    at: Vec2,
+    player: Player
+}
+
+#[derive(Event)]
+struct OrderSpawned {
+    food: Entity,
+    recipe: Id,
+    steps: usize
+}
+
+#[derive(Event)]
+struct StepCompleted {
+    food: Entity,
+    recipe: Id,
+    ingredient: Id,
+    tool: Id,
+    grade: Grade,
+    remaining: usize,
+    since_spawn: Duration,
+    player: Player
+}
+
+#[derive(Event)]
+struct OrderCompleted {
+    food: Entity,
+    recipe: Id,
+    tool: Id,
+    steps: usize,
+    grades: Vec<Grade>,
+    since_spawn: Duration,
+    patience: f32,
+    // where the last step happened and where the order sat in the queue
+    at: Vec2,
+    slot: Vec2,
+    player: Player
+}
+
+// the customer gave up waiting
+#[derive(Event)]
+struct OrderFailed {
+    food: Entity,
+    recipe: Id,
+    remaining: usize,
+    since_spawn: Duration
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Outcome {
+    Used(Entity),
+    Cooking(Entity),
+    Busy,
+    Wasted
+}
+
+// what became of an ingredient that got hit
+#[derive(Event)]
+struct Processed {
+    ingredient: Entity,
+    process: Entity,
+    outcome: Outcome,
+    grade: Grade,
+    at: Vec2,
+    player: Player
 }
 
 #[derive(Event)]
-struct RecipeComplete(Entity);
+struct Strike(StrikeReason);
 
-fn reset_score(mut score: ResMut<Score>) {
+#[derive(Event)]
+struct GameOver(LoseReason);
+
+#[allow(clippy::too_many_arguments)]
+fn reset_run(
+    mut score: ResMut<Score>,
+    mut breakdown: ResMut<ScoreBreakdown>,
+    mut combo: ResMut<Combo>,
+    mut failed: ResMut<FailedOrders>,
+    mut wasted: ResMut<Wasted>,
+    mut strikes: ResMut<Strikes>,
+    mut run_time: ResMut<RunTime>,
+    mut lost: ResMut<Lost>,
+    mut rng: ResMut<GameRng>,
+    mut retry: ResMut<RetrySeed>,
+    mut stats: ResMut<PlayerStats>,
+    mut recipes: ResMut<RecipesServed>,
+    mut orders: ResMut<OrderCount>) {
     score.0 = 0;
+    *breakdown = ScoreBreakdown::default();
+    *combo = Combo::default();
+    failed.0 = 0;
+    wasted.0 = 0;
+    strikes.0 = 0;
+    run_time.0.reset();
+    lost.0 = None;
+    *rng = retry.0.take().map(GameRng::new).unwrap_or_default();
+    *stats = PlayerStats::default();
+    recipes.0.clear();
+    orders.0 = 0;
+}
+
+// whatever is closest to the cursor is what a press goes for
+fn closest<T>(candidates: impl Iterator<Item = (T, Vec2)>, pos: Vec2) -> Option<(T, f32)> {
+    candidates.map(|(c, at)| (c, at.distance(pos))).min_by_key(|(_, d)| *d as usize)
 }
 
-fn process(mut evts: EventReader<Process>, mut next: EventWriter<ProcessIngredient>, active: Query<(Entity, &Active, &Transform), With<Ingredient>>) {
+fn process(
+    mut evts: EventReader<Process>,
+    mut next: EventWriter<ProcessIngredient>,
+    mut serve: EventWriter<Serve>,
+    mut miss: EventWriter<Miss>,
+    stations: Query<&Station>,
+    active: Query<(Entity, &Active, &Transform, &Throw), With<Ingredient>>) {
     for process in evts.read() {
-        let Some((e, a, _, dist)) = active
-            .iter()
-            .map(|(e, a, t)| (e, a, t, t.translation.xy().distance(process.1)))
-            .min_by_key(|(_, _, _, d)| *d as usize)
-        else { return };
+        // a finished dish on the station has to be taken off before anything else
+        if stations.get(process.0).is_ok_and(Station::done) {
+            serve.send(Serve(process.0, process.2));
+            continue;
+        }
+
+        let Some(((e, a, throw, at), dist)) = closest(active.iter().map(|(e, a, t, throw)| ((e, a, throw, t.translation.xy()), t.translation.xy())), process.1) else {
+            miss.send(Miss(process.0, process.1, process.2));
+            continue;
+        };
         info!("found closest entity: {:?}, {} away from cursor", e, dist);
-        if dist <= HITBOX_RAD {
-            //cmd.entity(e).despawn();
-            next.send(ProcessIngredient{ active: e, ingredient: a.0, process: process.0 });
+        if dist > HITBOX_RAD {
+            miss.send(Miss(process.0, process.1, process.2));
+        } else {
+            // the closer to the top of the throw the better
+            let apex = throw.v / (2. * throw.g);
+            let arc = ((throw.t.elapsed_secs() - apex).abs() / apex).min(1.);
+            let grade = Grade::new(arc, dist / HITBOX_RAD);
+            next.send(ProcessIngredient{ active: e, ingredient: a.0, process: process.0, grade, at, player: process.2 });
         }
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn process_ingredient(
     mut cmd: Commands,
     mut evts: EventReader<ProcessIngredient>,
-    mut completed: EventWriter<RecipeComplete>,
-    mut foods_a: Query<(Entity, &mut FoodIngredients, &Active), With<Food>>,
+    mut steps: EventWriter<StepCompleted>,
+    mut completed: EventWriter<OrderCompleted>,
+    mut strikes: EventWriter<Strike>,
+    mut processed: EventWriter<Processed>,
+    mut foods_a: Query<(Entity, &mut FoodIngredients, &mut Grades, &Active, &Patience, Option<&Owner>, &Transform), With<Food>>,
+    recipes: Query<(&Id, &FoodIngredients), Without<Active>>,
+    mut stations: Query<&mut Station>,
+    mut combo: ResMut<Combo>,
     ids: Query<&Id>) {
     for evt in evts.read() {
         let tool_id = ids.get(evt.process).unwrap();
         let ingredient_id = ids.get(evt.ingredient).unwrap();
 
+        // a busy station ruins whatever gets thrown at it
+        if stations.get(evt.process).is_ok_and(|s| s.slot.is_some()) {
+            cmd.entity(evt.active).despawn();
+            strikes.send(Strike(StrikeReason::WrongTool));
+            processed.send(Processed { ingredient: evt.ingredient, process: evt.process, outcome: Outcome::Busy, grade: evt.grade, at: evt.at, player: evt.player });
+            continue;
+        }
+
         let Some((foode, ii)) = foods_a
             .iter()
-            .find_map(|(e, ingredients, a)| {
+            .filter(|(.., owner, _)| owner.is_none_or(|o| o.0 == evt.player))
+            .find_map(|(e, ingredients, ..)| {
                 ingredients.0
                     .iter()
-                    .enumerate()
-                    .find(|(i, process)| process.ingredient == *ingredient_id && process.processing == *tool_id)
-                    .map(|(i, _)| (e, i))
+                    .position(|process| process.ingredient == *ingredient_id && process.processing == *tool_id)
+                    .map(|i| (e, i))
             }) else {
                 cmd.entity(evt.active).despawn();
+                strikes.send(Strike(StrikeReason::WrongTool));
+                processed.send(Processed { ingredient: evt.ingredient, process: evt.process, outcome: Outcome::Wasted, grade: evt.grade, at: evt.at, player: evt.player });
                 continue;
             };
 
-        let (_, mut ingredients, _) = foods_a.get_mut(foode).unwrap();
-        ingredients.0.remove(ii);
+        let (_, mut ingredients, mut grades, a, patience, _, slot) = foods_a.get_mut(foode).unwrap();
+        let slot = slot.translation.xy();
+        let step = ingredients.0.remove(ii);
         cmd.entity(evt.active).despawn();
-        if ingredients.0.len() == 0 {
-            cmd.entity(foode).despawn();
-            completed.send(RecipeComplete(foode));
+        combo.hit();
+        if let Ok(mut station) = stations.get_mut(evt.process) {
+            station.slot = Some(Cooking { food: foode, step, grade: evt.grade, t: Stopwatch::new() });
+            processed.send(Processed { ingredient: evt.ingredient, process: evt.process, outcome: Outcome::Cooking(foode), grade: evt.grade, at: evt.at, player: evt.player });
+            continue;
+        }
+
+        processed.send(Processed { ingredient: evt.ingredient, process: evt.process, outcome: Outcome::Used(foode), grade: evt.grade, at: evt.at, player: evt.player });
+        grades.0.push(evt.grade);
+        let (recipe, template) = recipes.get(a.0).unwrap();
+        let remaining = ingredients.0.len() + stations.iter().filter(|s| s.cooking(foode)).count();
+        steps.send(StepCompleted {
+            food: foode,
+            recipe: recipe.clone(),
+            ingredient: ingredient_id.clone(),
+            tool: tool_id.clone(),
+            grade: evt.grade,
+            remaining,
+            since_spawn: patience.0.elapsed(),
+            player: evt.player
+        });
+        if remaining == 0 {
+            cmd.entity(foode).despawn_recursive();
+            completed.send(OrderCompleted {
+                food: foode,
+                recipe: recipe.clone(),
+                tool: tool_id.clone(),
+                steps: template.0.len(),
+                grades: grades.0.clone(),
+                since_spawn: patience.0.elapsed(),
+                patience: patience.0.percent_left(),
+                at: evt.at,
+                slot,
+                player: evt.player
+            });
         }
     }
 }
 
-fn count_score(mut complete: EventReader<RecipeComplete>, mut score: ResMut<Score>) {
-    for _ in complete.read() {
-        score.0 += 1;
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn serve(
+    mut cmd: Commands,
+    mut evts: EventReader<Serve>,
+    mut steps: EventWriter<StepCompleted>,
+    mut completed: EventWriter<OrderCompleted>,
+    mut stations: Query<&mut Station>,
+    mut foods: Query<(&FoodIngredients, &mut Grades, &Active, &Patience, Option<&Owner>, &Transform), With<Food>>,
+    recipes: Query<(&Id, &FoodIngredients), Without<Active>>,
+    tools: Query<&GlobalTransform, With<Processing>>,
+    ids: Query<&Id>) {
+    for evt in evts.read() {
+        let Ok(mut station) = stations.get_mut(evt.0) else { continue };
+        let Some(cooking) = station.slot.take() else { continue };
+        // the order might be long gone by now
+        let Ok((ingredients, mut grades, a, patience, owner, slot)) = foods.get_mut(cooking.food) else { continue };
+        // the dish counts for whoever ordered it, no matter who took it off
+        let player = owner.map_or(evt.1, |o| o.0);
+
+        grades.0.push(cooking.grade);
+        let tool_id = ids.get(evt.0).unwrap();
+        let (recipe, template) = recipes.get(a.0).unwrap();
+        let remaining = ingredients.0.len() + stations.iter().filter(|s| s.cooking(cooking.food)).count();
+        steps.send(StepCompleted {
+            food: cooking.food,
+            recipe: recipe.clone(),
+            ingredient: cooking.step.ingredient,
+            tool: tool_id.clone(),
+            grade: cooking.grade,
+            remaining,
+            since_spawn: patience.0.elapsed(),
+            player
+        });
+        if remaining == 0 {
+            cmd.entity(cooking.food).despawn_recursive();
+            completed.send(OrderCompleted {
+                food: cooking.food,
+                recipe: recipe.clone(),
+                tool: tool_id.clone(),
+                steps: template.0.len(),
+                grades: grades.0.clone(),
+                since_spawn: patience.0.elapsed(),
+                patience: patience.0.percent_left(),
+                at: tools.get(evt.0).map_or(Vec2::ZERO, |t| t.translation().xy()),
+                slot: slot.translation.xy(),
+                player
+            });
+        }
+    }
+}
+
+fn cook(mut stations: Query<(Entity, &mut Station)>, mut foods: Query<&mut FoodIngredients, (With<Food>, With<Active>)>, mut wasted: ResMut<Wasted>, mut burnt: EventWriter<Burnt>, time: Res<Time>) {
+    for (e, mut station) in stations.iter_mut() {
+        let Some(cooking) = station.slot.as_mut() else { continue };
+        cooking.t.tick(time.delta());
+        if !station.burnt() { continue; }
+
+        // burnt, the order needs a fresh one
+        let cooking = station.slot.take().unwrap();
+        wasted.0 += 1;
+        if let Ok(mut ingredients) = foods.get_mut(cooking.food) {
+            ingredients.0.push(cooking.step);
+        }
+        burnt.send(Burnt(e));
+    }
+}
+
+fn count_score(
+    mut complete: EventReader<OrderCompleted>,
+    mut score: ResMut<Score>,
+    mut breakdown: ResMut<ScoreBreakdown>,
+    mut stats: ResMut<PlayerStats>,
+    mut recipes: ResMut<RecipesServed>,
+    mut scored: EventWriter<Scored>,
+    combo: Res<Combo>) {
+    for evt in complete.read() {
+        info!("completed {} in {:?} with {:?}", evt.recipe.0, evt.since_spawn, evt.grades);
+        // bigger recipes are worth more, the rest scales off of that
+        let base = POINTS_PER_STEP * evt.steps;
+        let grade = evt.grades.iter().map(|g| g.multiplier()).sum::<f32>() / evt.grades.len() as f32;
+        let graded = base as f32 * grade;
+        let combined = graded * combo.multiplier();
+        let speed = (base as f32 * evt.patience).round() as usize;
+
+        breakdown.served += 1;
+        breakdown.base += base;
+        breakdown.grade += (graded - base as f32).round() as usize;
+        breakdown.combo += (combined - graded).round() as usize;
+        breakdown.speed += speed;
+        let points = combined.round() as usize + speed;
+        score.0 += points;
+        stats.of(evt.player).score += points;
+        stats.of(evt.player).served += 1;
+        *recipes.0.entry(evt.recipe.clone()).or_default() += 1;
+        scored.send(Scored { points, at: evt.at });
+    }
+}
+
+fn count_waste(mut evts: EventReader<Processed>, mut wasted: ResMut<Wasted>, mut stats: ResMut<PlayerStats>) {
+    for evt in evts.read() {
+        let stats = stats.of(evt.player);
+        if let Outcome::Busy | Outcome::Wasted = evt.outcome {
+            wasted.0 += 1;
+            stats.wasted += 1;
+        } else {
+            stats.hits += 1;
+        }
+    }
+}
+
+fn apply_mode(mode: Res<GameMode>, mut cond: ResMut<LoseCondition>, mut filter: ResMut<RecipeFilter>, mut spawn: ResMut<SpawnParams>, zen: Res<ZenRecipes>) {
+    *cond = mode.lose_condition();
+    *spawn = mode.spawn_params();
+    filter.0 = match *mode {
+        GameMode::Zen => zen.0.clone(),
+        _ => HashSet::new()
+    };
+}
+
+fn pause(keys: Res<Input<KeyCode>>, mut focus: EventReader<WindowFocused>, settings: Res<Settings>, mut run_state: ResMut<NextState<RunState>>) {
+    let lost_focus = focus.read().any(|evt| !evt.focused);
+    if keys.just_pressed(KeyCode::Escape) || (lost_focus && settings.pause_on_focus_loss) {
+        run_state.set(RunState::Paused);
+    }
+}
+
+// nothing from the last run should be lying around when the next one starts
+fn clear_field(mut cmd: Commands, field: Query<Entity, With<InRun>>, mut stations: Query<&mut Station>) {
+    for e in field.iter() {
+        cmd.entity(e).despawn_recursive();
+    }
+    for mut station in stations.iter_mut() {
+        station.slot = None;
+    }
+}
+
+// the spawn timers speed up during a run, every run starts from the mode's base intervals
+fn reset_timers(mut food: ResMut<FoodSpawnTimer>, mut ingredient: ResMut<IngredientSpawnTimer>, mut despawn: ResMut<DespawnTimer>, params: Res<SpawnParams>) {
+    food.0 = Timer::from_seconds(params.food_interval(0), TimerMode::Repeating);
+    ingredient.0 = Timer::from_seconds(params.ingredient_interval(0), TimerMode::Repeating);
+    despawn.0.reset();
+}
+
+// events sent in the last frames of a run shouldn't be picked up by the next one
+fn clear_events<T: Event>(mut events: ResMut<Events<T>>) {
+    events.clear();
+}
+
+fn count_misses(mut evts: EventReader<Miss>, mut combo: ResMut<Combo>, mut stats: ResMut<PlayerStats>) {
+    for evt in evts.read() {
+        info!("{:?} missed with {:?} at {:?}", evt.2, evt.0, evt.1);
+        combo.current = 0;
+        stats.of(evt.2).misses += 1;
     }
 }
 
@@ -229,33 +1112,315 @@ fn keypress(mut keyevt: EventReader<KeyboardInput>, keymap: Res<KeyMapping>, mut
         if ev.state != ButtonState::Pressed { continue };
         let Some(key) = ev.key_code else { continue };
         let Some(tool) = keymap.0.get(&key) else { continue };
-        process.send(Process(*tool, coords));
+        process.send(Process(*tool, coords, Player::One));
     }
 }
 
-fn draw_active_foods(mut active: Query<(&Active, &mut Transform, &mut Visibility), With<Food>>, tex: Query<&Tex, With<Food>>, w: Query<&Window>, assets: Res<Assets<Image>>) {
-    let res = &w.single().resolution;
-    // redraw active foods
-    for (i, (a, mut transform, mut vis)) in active.iter_mut().enumerate() {
+#[allow(clippy::type_complexity)]
+fn draw_active_foods(
+    mut active: Query<(&Active, &Patience, &Children, Option<&Owner>, &mut Transform, &mut Visibility), With<Food>>,
+    mut bars: Query<&mut Sprite, With<PatienceBar>>,
+    tex: Query<&Tex, With<Food>>,
+    assets: Res<Assets<Image>>,
+    mode: Res<GameMode>,
+    cond: Res<LoseCondition>) {
+    // with split queues each player only gets half the width, so the orders shrink until a full queue fits
+    let spacing = if mode.split_queues() {
+        let room = cond.max_queue.unwrap_or(MAX_QUEUE) + 1;
+        (VIEW.x / 2. / room as f32).min(ORDER_SPACING)
+    } else {
+        ORDER_SPACING
+    };
+    // redraw active foods, player two's queue grows in from the right
+    let mut counts = [0; 2];
+    for (a, patience, children, owner, mut transform, mut vis) in active.iter_mut() {
         let tex = tex.get(a.0).unwrap();
-        let Some(asset) = assets.get(&tex.0) else { continue; };
-
-        transform.translation.y = -res.height() / 2. + 50.;
-        transform.translation.x = -res.width() / 2. + ((i + 1) * 75) as f32 - 15.;
+        if !assets.contains(&tex.0) { continue; }
+
+        let player = owner.map_or(Player::One, |o| o.0);
+        counts[player.index()] += 1;
+        let offset = counts[player.index()] as f32 * spacing - spacing / 5.;
+        transform.translation.y = -VIEW.y / 2. + 85.;
+        transform.scale = Vec3::splat(spacing / ORDER_SPACING);
+        transform.translation.x = match player {
+            Player::One => -VIEW.x / 2. + offset,
+            Player::Two => VIEW.x / 2. - offset
+        };
         //transform.translation.z = 1000.;
         *vis = Visibility::Visible;
+
+        let left = patience.0.percent_left();
+        for child in children.iter() {
+            let Ok(mut bar) = bars.get_mut(*child) else { continue };
+            bar.custom_size = Some(Vec2::new(PATIENCE_BAR_WIDTH * left, 6.));
+            bar.color = Color::rgb(1. - left, left, 0.);
+        }
     }
 }
 
-fn draw_processing(mut tools: Query<(Entity, &mut Transform), With<Processing>>, tex: Query<&Tex>, w: Query<&Window>, assets: Res<Assets<Image>>) {
-    let res = &w.single().resolution;
+#[allow(clippy::type_complexity)]
+fn add_step_icons(
+    mut cmd: Commands,
+    orders: Query<(Entity, &FoodIngredients), (Added<Active>, With<Food>)>,
+    ingredients: Res<Ingredients>,
+    tools: Query<(&Id, &Tex), With<Processing>>,
+    tex: Query<&Tex>) {
+    for (e, steps) in orders.iter() {
+        cmd.entity(e).with_children(|parent| {
+            for (i, step) in steps.0.iter().enumerate() {
+                let column = (i % STEP_COLUMNS) as f32 - (STEP_COLUMNS - 1) as f32 / 2.;
+                let row = (i / STEP_COLUMNS) as f32;
+                let icon = |handle: Handle<Image>, x: f32| SpriteBundle {
+                    texture: handle,
+                    sprite: Sprite { custom_size: Some(Vec2::splat(STEP_ICON)), ..default() },
+                    transform: Transform::from_xyz(x, 0., 0.),
+                    ..default()
+                };
+                let ingredient = ingredients.0.get(&step.ingredient).and_then(|e| tex.get(*e).ok());
+                let tool = tools.iter().find(|(id, _)| **id == step.processing).map(|(_, tex)| tex);
+
+                parent.spawn((
+                    StepIcon(step.clone()),
+                    SpatialBundle::from_transform(Transform::from_xyz(column * (STEP_ICON * 2. + 1.), -50. - row * (STEP_ICON + 1.), 1.))
+                )).with_children(|parent| {
+                    if let Some(tex) = ingredient { parent.spawn(icon(tex.0.clone(), -STEP_ICON / 2.)); }
+                    if let Some(tex) = tool { parent.spawn(icon(tex.0.clone(), STEP_ICON / 2.)); }
+                    // strike through, shown once the step is done
+                    parent.spawn((
+                        StepCheck,
+                        SpriteBundle {
+                            sprite: Sprite { color: Color::LIME_GREEN, custom_size: Some(Vec2::new(STEP_ICON * 2., 2.)), ..default() },
+                            transform: Transform::from_xyz(0., 0., 1.),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        }
+                    ));
+                });
+            }
+        });
+    }
+}
+
+// a step is done once it's neither on the order nor sitting on a station
+#[allow(clippy::type_complexity)]
+fn draw_step_icons(
+    orders: Query<(Entity, &FoodIngredients, &Children), (With<Food>, With<Active>)>,
+    stations: Query<&Station>,
+    icons: Query<(&StepIcon, &Children)>,
+    mut sprites: Query<(&mut Sprite, &mut Visibility, Option<&StepCheck>)>) {
+    for (e, steps, children) in orders.iter() {
+        let mut waiting = steps.0.clone();
+        let mut cooking: Vec<_> = stations.iter().filter_map(|s| s.slot.as_ref()).filter(|c| c.food == e).map(|c| c.step.clone()).collect();
+
+        for (icon, parts) in children.iter().filter_map(|c| icons.get(*c).ok()) {
+            let (color, done) = if let Some(i) = waiting.iter().position(|s| *s == icon.0) {
+                waiting.remove(i);
+                (Color::WHITE, false)
+            } else if let Some(i) = cooking.iter().position(|s| *s == icon.0) {
+                cooking.remove(i);
+                (Color::rgb(1., 1., 0.4), false)
+            } else {
+                (Color::rgba(1., 1., 1., 0.3), true)
+            };
+
+            for part in parts.iter() {
+                let Ok((mut sprite, mut vis, check)) = sprites.get_mut(*part) else { continue };
+                if check.is_some() {
+                    *vis = if done { Visibility::Inherited } else { Visibility::Hidden };
+                } else {
+                    sprite.color = color;
+                }
+            }
+        }
+    }
+}
+
+fn expire_orders(
+    mut cmd: Commands,
+    mut foods: Query<(Entity, &Active, &FoodIngredients, &mut Patience), With<Food>>,
+    stations: Query<&Station>,
+    ids: Query<&Id>,
+    mut failed: EventWriter<OrderFailed>,
+    time: Res<Time>) {
+    for (e, a, ingredients, mut patience) in foods.iter_mut() {
+        if !patience.0.tick(time.delta()).just_finished() { continue; }
+        cmd.entity(e).despawn_recursive();
+        failed.send(OrderFailed {
+            food: e,
+            recipe: ids.get(a.0).unwrap().clone(),
+            remaining: ingredients.0.len() + stations.iter().filter(|s| s.cooking(e)).count(),
+            since_spawn: patience.0.elapsed()
+        });
+    }
+}
+
+fn order_expired(
+    mut evts: EventReader<OrderFailed>,
+    cfg: Res<PatienceConfig>,
+    mut score: ResMut<Score>,
+    mut breakdown: ResMut<ScoreBreakdown>,
+    mut failed: ResMut<FailedOrders>,
+    mut strikes: EventWriter<Strike>,
+    mut over: EventWriter<GameOver>) {
+    for evt in evts.read() {
+        info!("order {:?} for {} expired with {} steps left", evt.food, evt.recipe.0, evt.remaining);
+        failed.0 += 1;
+        strikes.send(Strike(StrikeReason::Expired));
+        match cfg.expiry {
+            Expiry::Nothing => {},
+            Expiry::Penalty(points) => {
+                breakdown.penalty += points.min(score.0);
+                score.0 = score.0.saturating_sub(points);
+            },
+            Expiry::GameOver => over.send(GameOver(LoseReason::OrderExpired))
+        }
+    }
+}
+
+fn draw_processing(mut tools: Query<(Entity, &mut Transform), With<Processing>>, tex: Query<&Tex>, assets: Res<Assets<Image>>) {
     // redraw active foods
     for (i, (e, mut transform)) in tools.iter_mut().enumerate() {
         let tex = tex.get(e).unwrap();
         let Some(asset) = assets.get(&tex.0) else { continue; };
 
-        transform.translation.y = res.height() / 2. - ((i + 1) * (asset.size().x as usize + 15)) as f32;
-        transform.translation.x = -res.width() / 2. + 50. ;
+        transform.translation.y = VIEW.y / 2. - ((i + 1) * (asset.size().x as usize + 15)) as f32;
+        transform.translation.x = -VIEW.x / 2. + 50. ;
+    }
+}
+
+fn spawn_target(mut cmd: Commands, tools: Query<(Entity, &Tex), With<Processing>>) {
+    cmd.spawn((Target, InRun, SpatialBundle { visibility: Visibility::Hidden, ..default() }))
+        .with_children(|parent| {
+            parent.spawn((
+                TargetBackdrop,
+                SpriteBundle {
+                    sprite: Sprite { custom_size: Some(Vec2::splat(72.)), ..default() },
+                    ..default()
+                }
+            ));
+            for (tool, tex) in tools.iter() {
+                parent.spawn((
+                    TargetTool(tool),
+                    SpriteBundle {
+                        texture: tex.0.clone(),
+                        sprite: Sprite { custom_size: Some(Vec2::splat(TARGET_TOOL)), ..default() },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    }
+                ));
+            }
+        });
+}
+
+// green with the tools it needs when an order wants the ingredient under the cursor, red when nobody does
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn highlight_target(
+    w: Query<&Window>,
+    cameraq: Query<(&Camera, &GlobalTransform)>,
+    active: Query<(&Active, &Transform), (With<Ingredient>, With<Throw>, Without<Target>)>,
+    orders: Query<(&FoodIngredients, Option<&Owner>), (With<Food>, With<Active>)>,
+    ids: Query<&Id>,
+    mut target: Query<(&mut Transform, &mut Visibility), (With<Target>, Without<Ingredient>)>,
+    mut backdrop: Query<&mut Sprite, With<TargetBackdrop>>,
+    mut icons: Query<(&TargetTool, &mut Transform, &mut Visibility), (Without<Target>, Without<Ingredient>)>) {
+    let Ok((mut transform, mut vis)) = target.get_single_mut() else { return };
+    let (cam, cam_transform) = cameraq.single();
+    let found = w.single().cursor_position()
+        .and_then(|cursor| cam.viewport_to_world_2d(cam_transform, cursor))
+        .and_then(|coords| closest(active.iter().map(|(a, t)| ((a, t), t.translation.xy())), coords))
+        .filter(|(_, dist)| *dist <= HITBOX_RAD);
+    let Some(((a, at), _)) = found else {
+        *vis = Visibility::Hidden;
+        return;
+    };
+    *vis = Visibility::Visible;
+    transform.translation = at.translation.xy().extend(at.translation.z - 1.);
+
+    // only player one aims with the mouse, so only their orders count
+    let ingredient = ids.get(a.0).unwrap();
+    let wanted: Vec<&Id> = orders.iter()
+        .filter(|(_, owner)| owner.is_none_or(|o| o.0 == Player::One))
+        .flat_map(|(steps, _)| steps.0.iter())
+        .filter(|step| step.ingredient == *ingredient)
+        .map(|step| &step.processing)
+        .collect();
+
+    for mut sprite in backdrop.iter_mut() {
+        sprite.color = if wanted.is_empty() { Color::rgba(1., 0.2, 0.2, 0.35) } else { Color::rgba(0.2, 1., 0.2, 0.35) };
+    }
+
+    let tools: Vec<_> = icons.iter().filter(|(tool, ..)| ids.get(tool.0).is_ok_and(|id| wanted.contains(&id))).map(|(tool, ..)| tool.0).collect();
+    let mut x = -(tools.len() as f32 - 1.) * TARGET_TOOL / 2.;
+    for (tool, mut transform, mut vis) in icons.iter_mut() {
+        if tools.contains(&tool.0) {
+            *vis = Visibility::Visible;
+            transform.translation = Vec3::new(x, 46., 2.);
+            x += TARGET_TOOL;
+        } else {
+            *vis = Visibility::Hidden;
+        }
+    }
+}
+
+fn label_keys(keymap: Res<KeyMapping>, tools: Query<(Entity, &Children), With<Processing>>, mut labels: Query<&mut Text, With<KeyLabel>>) {
+    if !keymap.is_changed() { return; }
+    for (tool, children) in tools.iter() {
+        let mut texts = labels.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = key_label(&keymap, tool).unwrap_or_default();
+        }
+    }
+}
+
+fn flash_tools(mut presses: EventReader<Process>, mut misses: EventReader<Miss>, mut tools: Query<&mut ToolFlash>) {
+    for evt in presses.read() {
+        if let Ok(mut flash) = tools.get_mut(evt.0) { flash.press.reset(); }
+    }
+    for evt in misses.read() {
+        if let Ok(mut flash) = tools.get_mut(evt.0) { flash.miss.reset(); }
+    }
+}
+
+// a quick pop on every press, a red wobble when the press hit nothing
+fn animate_tools(mut tools: Query<(&mut ToolFlash, &mut Transform, &mut Sprite)>, time: Res<Time>) {
+    for (mut flash, mut transform, mut sprite) in tools.iter_mut() {
+        flash.press.tick(time.delta());
+        flash.miss.tick(time.delta());
+
+        transform.scale = Vec3::splat(1. + TOOL_POP * flash.press.percent_left());
+        let miss = flash.miss.percent_left();
+        transform.rotation = Quat::from_rotation_z(TOOL_SHAKE * miss * (flash.miss.elapsed_secs() * 40.).sin());
+        sprite.color = Color::rgb(1., 1. - miss, 1. - miss);
+    }
+}
+
+fn draw_stations(
+    stations: Query<(&Station, &Children)>,
+    mut slots: Query<(&mut Handle<Image>, &mut Sprite, &mut Visibility), With<Slot>>,
+    ingredients: Res<Ingredients>,
+    tex: Query<&Tex>) {
+    for (station, children) in stations.iter() {
+        for child in children.iter() {
+            let Ok((mut handle, mut sprite, mut vis)) = slots.get_mut(*child) else { continue };
+            let Some(cooking) = &station.slot else {
+                *vis = Visibility::Hidden;
+                continue;
+            };
+
+            let template = ingredients.0.get(&cooking.step.ingredient).unwrap();
+            *handle = tex.get(*template).unwrap().0.clone();
+            *vis = Visibility::Visible;
+
+            // grey while cooking, full color when done, charring towards black while it burns
+            let t = cooking.t.elapsed_secs();
+            let cook = station.cook.as_secs_f32();
+            let c = if t < cook {
+                0.4 + 0.6 * t / cook
+            } else {
+                1. - 0.8 * (t - cook) / station.burn.as_secs_f32()
+            };
+            sprite.color = Color::rgb(c, c, c);
+        }
     }
 }
 
@@ -280,12 +1445,13 @@ fn add_food(cmd: &mut Commands, name: Id, assets: &AssetServer, ingredients: Foo
     });
 }
 
-fn add_processing(cmd: &mut Commands, name: Id, assets: &AssetServer, res: &WindowResolution) -> (Entity, Id) {
+fn add_processing(cmd: &mut Commands, name: Id, assets: &AssetServer, station: Option<Station>) -> (Entity, Id) {
     let handle: Handle<Image> = assets.load(name.0.clone() + ".png");
-    let e = cmd.spawn((
+    let mut e = cmd.spawn((
         ProcessingBundle {
             id: name.clone(),
             tex: Tex(handle.clone()),
+            flash: ToolFlash::default(),
             marker: Processing,
         },
         SpriteBundle {
@@ -293,13 +1459,38 @@ fn add_processing(cmd: &mut Commands, name: Id, assets: &AssetServer, res: &Wind
             transform: Transform::default(),
             ..default()
         }
-    )).id();
+    ));
+
+    if let Some(station) = station {
+        // the cooking slot sits right next to the tool icon
+        e.insert(station).with_children(|parent| {
+            parent.spawn((
+                Slot,
+                SpriteBundle {
+                    transform: Transform::from_xyz(64., 0., 1.).with_scale(Vec3::splat(0.6)),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                }
+            ));
+        });
+    }
+
+    // bound key in the bottom left corner, filled in by label_keys
+    e.with_children(|parent| {
+        parent.spawn((
+            KeyLabel,
+            Text2dBundle {
+                text: Text::from_section("", TextStyle { font_size: 20., color: Color::WHITE, ..default() }),
+                transform: Transform::from_xyz(-36., -22., 2.),
+                ..default()
+            }
+        ));
+    });
 
-    (e, name)
+    (e.id(), name)
 }
 
-fn setup(mut cmd: Commands, assets: Res<AssetServer>, mut ingredients: ResMut<Ingredients>, w: Query<&Window>) {
-    let res = &w.single().resolution;
+fn setup(mut cmd: Commands, assets: Res<AssetServer>, mut ingredients: ResMut<Ingredients>) {
     cmd.spawn(Camera2dBundle::default());
 
     cmd.insert_resource(IngredientSpawnTimer(Timer::from_seconds(INGREDIENT_SPAWN, TimerMode::Repeating)));
@@ -309,14 +1500,14 @@ fn setup(mut cmd: Commands, assets: Res<AssetServer>, mut ingredients: ResMut<In
     let tomato = add_ingredient(&mut cmd, Id("tomato".to_string()), &assets, &mut ingredients);
     let egg = add_ingredient(&mut cmd, Id("egg".to_string()), &assets, &mut ingredients);
     let bread = add_ingredient(&mut cmd, Id("bread".to_string()), &assets, &mut ingredients);
-    let carrot = add_ingredient(&mut cmd, Id("carrot".to_string()), &assets, &mut ingredients);
+    add_ingredient(&mut cmd, Id("carrot".to_string()), &assets, &mut ingredients);
     let fish = add_ingredient(&mut cmd, Id("fish".to_string()), &assets, &mut ingredients);
     let garlic = add_ingredient(&mut cmd, Id("garlic".to_string()), &assets, &mut ingredients);
     let orange = add_ingredient(&mut cmd, Id("orange".to_string()), &assets, &mut ingredients);
     let nori = add_ingredient(&mut cmd, Id("nori".to_string()), &assets, &mut ingredients);
-    let flour = add_ingredient(&mut cmd, Id("flour".to_string()), &assets, &mut ingredients);
+    add_ingredient(&mut cmd, Id("flour".to_string()), &assets, &mut ingredients);
     let cucumber = add_ingredient(&mut cmd, Id("cucumber".to_string()), &assets, &mut ingredients);
-    let mayo = add_ingredient(&mut cmd, Id("mayo".to_string()), &assets, &mut ingredients);
+    add_ingredient(&mut cmd, Id("mayo".to_string()), &assets, &mut ingredients);
     let cabbage = add_ingredient(&mut cmd, Id("cabbage".to_string()), &assets, &mut ingredients);
     let ketchup = add_ingredient(&mut cmd, Id("ketchup".to_string()), &assets, &mut ingredients);
     let broth = add_ingredient(&mut cmd, Id("broth".to_string()), &assets, &mut ingredients);
@@ -326,11 +1517,11 @@ fn setup(mut cmd: Commands, assets: Res<AssetServer>, mut ingredients: ResMut<In
     let meat = add_ingredient(&mut cmd, Id("meat".to_string()), &assets, &mut ingredients);
     let rice = add_ingredient(&mut cmd, Id("rice".to_string()), &assets, &mut ingredients);
 
-    let (pane, pan) = add_processing(&mut cmd, Id("pan".to_string()), &assets, res);
-    let (knifee, knife) = add_processing(&mut cmd, Id("knife".to_string()), &assets, res);
-    let (pote, pot) = add_processing(&mut cmd, Id("pot".to_string()), &assets, res);
-    //let (bowle, bowl) = add_processing(&mut cmd, Id("bowl".to_string()), &assets, res);
-    let (toastere, toaster) = add_processing(&mut cmd, Id("toaster".to_string()), &assets, res);
+    let (pane, pan) = add_processing(&mut cmd, Id("pan".to_string()), &assets, Some(Station::new(1.5, 3.)));
+    let (knifee, knife) = add_processing(&mut cmd, Id("knife".to_string()), &assets, None);
+    let (pote, pot) = add_processing(&mut cmd, Id("pot".to_string()), &assets, Some(Station::new(2.5, 4.)));
+    //let (bowle, bowl) = add_processing(&mut cmd, Id("bowl".to_string()), &assets, None);
+    let (toastere, toaster) = add_processing(&mut cmd, Id("toaster".to_string()), &assets, None);
 
     let mut keymap = KeyMapping(HashMap::new());
     keymap.0.insert(KeyCode::Key1, pane);
@@ -341,6 +1532,17 @@ fn setup(mut cmd: Commands, assets: Res<AssetServer>, mut ingredients: ResMut<In
 
     cmd.insert_resource(keymap);
 
+    let mut p2 = PlayerTwoControls::default();
+    p2.keys.insert(KeyCode::Key7, pane);
+    p2.keys.insert(KeyCode::Key8, knifee);
+    p2.keys.insert(KeyCode::Key9, pote);
+    p2.keys.insert(KeyCode::Key0, toastere);
+    p2.buttons.insert(GamepadButtonType::South, pane);
+    p2.buttons.insert(GamepadButtonType::East, knifee);
+    p2.buttons.insert(GamepadButtonType::West, pote);
+    p2.buttons.insert(GamepadButtonType::North, toastere);
+    cmd.insert_resource(p2);
+
     add_food(&mut cmd,
         Id("fried_egg".to_string()),
         &assets,
@@ -410,62 +1612,144 @@ fn setup(mut cmd: Commands, assets: Res<AssetServer>, mut ingredients: ResMut<In
     cmd.insert_resource(ThrowConfig { time: 1., height: 100., drift: 100. });
 }
 
-fn loose(mut cmd: Commands, query: Query<Entity, (With<Food>, With<Active>)>, mut game_state: ResMut<NextState<GameState>>) {
-    if query.iter().len() > 7 {
-        for e in query.iter() { cmd.entity(e).despawn(); }
-        game_state.set(GameState::MainMenu);
+fn loose(
+    query: Query<Option<&Owner>, (With<Food>, With<Active>)>,
+    cond: Res<LoseCondition>,
+    strikes: Res<Strikes>,
+    mut run_time: ResMut<RunTime>,
+    time: Res<Time>,
+    mut over: EventWriter<GameOver>) {
+    run_time.0.tick(time.delta());
+
+    let mut queues = [0; 2];
+    let mut shared = 0;
+    for owner in query.iter() {
+        match owner {
+            Some(o) => queues[o.0.index()] += 1,
+            None => shared += 1
+        }
+    }
+    let overrun = [Player::One, Player::Two].into_iter().find(|p| cond.max_queue.is_some_and(|max| queues[p.index()] > max));
+
+    if cond.max_queue.is_some_and(|max| shared > max) {
+        over.send(GameOver(LoseReason::QueueFull));
+    } else if let Some(player) = overrun {
+        over.send(GameOver(LoseReason::Overrun(player)));
+    } else if cond.lives(&strikes) == Some(0) {
+        over.send(GameOver(LoseReason::OutOfStrikes));
+    } else if cond.time_left(&run_time) == Some(0.) {
+        over.send(GameOver(LoseReason::TimeUp));
+    }
+}
+
+fn count_strikes(mut evts: EventReader<Strike>, mut strikes: ResMut<Strikes>, mut combo: ResMut<Combo>) {
+    for evt in evts.read() {
+        info!("strike: {:?}", evt.0);
+        strikes.0 += 1;
+        if let StrikeReason::WrongTool = evt.0 { combo.current = 0; }
     }
 }
 
-fn spawn_foods(mut cmd: Commands, foods: Query<(Entity, &Tex, &FoodIngredients), With<Food>>, time: Res<Time>, mut timer: ResMut<FoodSpawnTimer>, score: Res<Score>) {
+fn game_over(
+    mut evts: EventReader<GameOver>,
+    mut lost: ResMut<Lost>,
+    score: Res<Score>,
+    highscores: Res<HighScores>,
+    mode: Res<GameMode>,
+    mut game_state: ResMut<NextState<GameState>>) {
+    // several conditions can trip in the same frame, the first one wins
+    let Some(reason) = evts.read().next().map(|evt| evt.0) else { return };
+    evts.clear();
+    if !mode.can_lose() { return; }
+
+    info!("game over: {:?}", reason);
+    lost.0 = Some(reason);
+    game_state.set(if highscores.qualifies(score.0) { GameState::NameEntry } else { GameState::GameOver });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_foods(mut cmd: Commands, foods: Query<(Entity, &Id, &Tex, &FoodIngredients), With<Food>>, time: Res<Time>, mut timer: ResMut<FoodSpawnTimer>, score: Res<ScoreBreakdown>, patience: Res<PatienceConfig>, mut rng: ResMut<GameRng>, mut spawned: EventWriter<OrderSpawned>, params: Res<SpawnParams>, filter: Res<RecipeFilter>, mode: Res<GameMode>, mut count: ResMut<OrderCount>) {
     if ! timer.0.tick(time.delta()).just_finished() { return; }
 
-    timer.0.set_duration(Duration::from_secs_f32((FOOD_SPAWN - (score.0 as f32 * 0.05)).max(1.)));
+    timer.0.set_duration(Duration::from_secs_f32(params.food_interval(score.served)));
     timer.0.reset();
 
-    let (e, tex, ingredients) = foods.iter().choose(&mut rand::thread_rng()).unwrap();
+    // sorted so the same seed always picks the same recipes, whatever order the query hands them out in
+    let mut pool: Vec<_> = foods.iter().filter(|(_, id, ..)| filter.allows(id)).collect();
+    pool.sort_by_key(|(_, id, ..)| id.0.clone());
+    let Some(&(e, id, tex, ingredients)) = pool.choose(&mut rng.orders) else { return };
+    let owners = if mode.split_queues() { vec![Some(Player::One), Some(Player::Two)] } else { vec![None] };
+    // with split queues both players get the same order so it stays fair
+    for owner in owners {
+        let food = spawn_order(&mut cmd, &mut count, e, tex, ingredients, patience.base + patience.per_step * ingredients.0.len() as f32);
+        if let Some(player) = owner {
+            cmd.entity(food).insert(Owner(player));
+        }
+        spawned.send(OrderSpawned { food, recipe: id.clone(), steps: ingredients.0.len() });
+    }
+}
+
+fn spawn_order(cmd: &mut Commands, count: &mut OrderCount, template: Entity, tex: &Tex, ingredients: &FoodIngredients, patience: f32) -> Entity {
+    count.0 += 1;
     cmd.spawn((
-        Active(e),
+        Active(template),
+        SpawnIndex(count.0),
         Food{},
         ingredients.clone(),
+        Grades::default(),
+        InRun,
+        Patience(Timer::from_seconds(patience, TimerMode::Once)),
         SpriteBundle {
             texture: tex.0.clone(),
             visibility: Visibility::Hidden,
             ..default()
         }
-    ));
+    )).with_children(|parent| {
+        // countdown bar under the dish
+        parent.spawn((
+            PatienceBar,
+            SpriteBundle {
+                sprite: Sprite { anchor: Anchor::CenterLeft, ..default() },
+                transform: Transform::from_xyz(-PATIENCE_BAR_WIDTH / 2., -40., 1.),
+                ..default()
+            }
+        ));
+    }).id()
 }
 
-fn spawn_ingredients(mut cmd: Commands, time: Res<Time>, mut timer: ResMut<IngredientSpawnTimer>, cfg: Res<ThrowConfig>, foods: Query<&Active, With<Food>>, food_ingredients: Query<&FoodIngredients>, ingredients: Res<Ingredients>, tex: Query<&Tex, With<Ingredient>>, w: Query<&Window>, score: Res<Score>) {
+#[allow(clippy::too_many_arguments)]
+fn spawn_ingredients(mut cmd: Commands, time: Res<Time>, mut timer: ResMut<IngredientSpawnTimer>, foods: Query<(&SpawnIndex, &Active), With<Food>>, food_ingredients: Query<&FoodIngredients>, ingredients: Res<Ingredients>, tex: Query<&Tex, With<Ingredient>>, score: Res<ScoreBreakdown>, mut rng: ResMut<GameRng>, params: Res<SpawnParams>) {
     if ! timer.0.tick(time.delta()).just_finished() { return; }
 
-    timer.0.set_duration(Duration::from_secs_f32((INGREDIENT_SPAWN - (score.0 as f32 * 0.5)).max(0.3)));
+    timer.0.set_duration(Duration::from_secs_f32(params.ingredient_interval(score.served)));
     timer.0.reset();
 
-    let Some(food) = foods.iter().choose(&mut rand::thread_rng()) else { return };
-    let ingredient_id = &food_ingredients.get(food.0).unwrap().0.choose(&mut rand::thread_rng()).unwrap().ingredient;
+    let mut orders: Vec<_> = foods.iter().collect();
+    orders.sort_by_key(|(i, _)| **i);
+    let Some((_, food)) = orders.choose(&mut rng.throws) else { return };
+    let ingredient_id = &food_ingredients.get(food.0).unwrap().0.choose(&mut rng.throws).unwrap().ingredient;
     let ingredient = ingredients.0.get(ingredient_id).unwrap();
 
-    let w = w.single();
-    let height = w.height() + w.height() / 2.;
-    let drift: f32= rand::thread_rng().gen_range(-100. .. 100.);
-    let time = (0.1 + (score.0 as f32 * 0.1)).min(5.);
+    let drift: f32= rng.throws.gen_range(-100. .. 100.);
+    let spawn_x = rng.throws.gen_range(- VIEW.x / 2. + 25. .. VIEW.x / 2. + 25.);
+    throw_ingredient(&mut cmd, *ingredient, tex.get(*ingredient).unwrap(), spawn_x, drift, params.throw_time(score.served));
+}
 
+// lob an ingredient up from the bottom edge, a higher time makes a faster arc
+fn throw_ingredient(cmd: &mut Commands, ingredient: Entity, tex: &Tex, spawn_x: f32, drift: f32, time: f32) {
+    let height = VIEW.y + VIEW.y / 2.;
     let g = height / 2.0 * time.powi(2);
     let v = f32::sqrt(2. * height * g);
-    let drift = f32::sqrt(2. * drift.abs() * g) * drift.min(1.).max(-1.);
-
-    let res = &w.resolution;
-    let mut rng = rand::thread_rng();
+    let drift = f32::sqrt(2. * drift.abs() * g) * drift.clamp(-1., 1.);
 
-    let spawn_x = rng.gen_range(- res.width() / 2. + 25. .. res.width() / 2. + 25.);
-    let spawn_y = - res.height() / 2.;
+    let spawn_y = - VIEW.y / 2.;
 
     cmd.spawn((
-        Active(*ingredient),
+        Active(ingredient),
         Ingredient {},
+        InRun,
         SpriteBundle {
-            texture: tex.get(*ingredient).unwrap().0.clone(),
+            texture: tex.0.clone(),
             transform: Transform::from_xyz(spawn_x, spawn_y, 10.),
             ..default()
         },
@@ -480,11 +1764,29 @@ fn spawn_ingredients(mut cmd: Commands, time: Res<Time>, mut timer: ResMut<Ingre
     ));
 }
 
-fn despawn_ingredients(mut cmd: Commands, q: Query<(Entity, &Transform), With<Throw>>, time: Res<Time>, mut timer: ResMut<DespawnTimer>, w: Query<&Window>) {
+#[allow(clippy::too_many_arguments)]
+fn despawn_ingredients(
+    mut cmd: Commands,
+    q: Query<(Entity, &Active, &Transform), With<Throw>>,
+    foods: Query<&FoodIngredients, (With<Food>, With<Active>)>,
+    ids: Query<&Id>,
+    time: Res<Time>,
+    mut timer: ResMut<DespawnTimer>,
+    cond: Res<LoseCondition>,
+    mut strikes: EventWriter<Strike>) {
     if ! timer.0.tick(time.delta()).finished() { return; }
-    let res = &w.single().resolution;
-    for (e, _) in q.iter().filter(|(_, t)| t.translation.y < - res.height() / 2.) {
-        cmd.entity(e).despawn()
+    let fallen = |t: &Transform| t.translation.y < - VIEW.y / 2.;
+    let mut struck = HashSet::new();
+    for (e, a, _) in q.iter().filter(|(.., t)| fallen(t)) {
+        cmd.entity(e).despawn();
+
+        if ! cond.drop_strikes { continue; }
+        // another copy still in the air can make up for it
+        if q.iter().any(|(_, other, t)| other.0 == a.0 && !fallen(t)) { continue; }
+        let id = ids.get(a.0).unwrap();
+        if foods.iter().any(|f| f.0.iter().any(|step| step.ingredient == *id)) && struck.insert(a.0) {
+            strikes.send(Strike(StrikeReason::Dropped));
+        }
     }
 }
 
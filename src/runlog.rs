@@ -0,0 +1,294 @@
+use std::{ fs, path::PathBuf };
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::save;
+use crate::{
+    Active, GameOver, GameState, Id, Miss, OrderCompleted, OrderFailed, OrderSpawned, Outcome, Process,
+    Player, ProcessIngredient, Processed, RunTime, Serve, StepCompleted, Strike, Throw,
+};
+
+pub struct RunLogPlugin;
+impl Plugin for RunLogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RunLog>()
+            .init_resource::<RunLogConfig>()
+            .add_systems(OnEnter(GameState::Game), clear_log)
+            // after everything in Update has sent its events, so the last frame of a run makes it into the log too
+            .add_systems(PostUpdate, (record_spawns, record_events).run_if(in_state(GameState::Game)))
+            .add_systems(OnExit(GameState::Game), export_log);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogFormat {
+    Off,
+    Jsonl,
+    Csv,
+}
+
+#[derive(Resource)]
+pub struct RunLogConfig {
+    pub format: LogFormat,
+}
+
+impl Default for RunLogConfig {
+    fn default() -> Self {
+        let format = match std::env::var("RUN_LOG").as_deref() {
+            Ok("off") => LogFormat::Off,
+            Ok("csv") => LogFormat::Csv,
+            _ => LogFormat::Jsonl,
+        };
+        Self { format }
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct LogEntry {
+    pub t: f32,
+    pub event: &'static str,
+    pub entity: Option<u64>,
+    pub player: Option<usize>,
+    pub recipe: Option<String>,
+    pub ingredient: Option<String>,
+    pub tool: Option<String>,
+    pub remaining: Option<usize>,
+    pub since_spawn: Option<f32>,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    pub detail: Option<String>,
+}
+
+#[derive(Resource, Default)]
+pub struct RunLog {
+    pub started: String,
+    pub entries: Vec<LogEntry>,
+}
+
+fn name(ids: &Query<&Id>, e: Entity) -> Option<String> {
+    ids.get(e).ok().map(|id| id.0.clone())
+}
+
+fn player(p: Player) -> Option<usize> {
+    Some(p.index() + 1)
+}
+
+fn clear_log(mut log: ResMut<RunLog>) {
+    log.started = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    log.entries.clear();
+}
+
+fn record_spawns(
+    mut log: ResMut<RunLog>,
+    run_time: Res<RunTime>,
+    ids: Query<&Id>,
+    throws: Query<(Entity, &Active, &Throw), Added<Throw>>,
+) {
+    let t = run_time.0.elapsed_secs();
+    for (e, a, throw) in throws.iter() {
+        log.entries.push(LogEntry {
+            t,
+            event: "ingredient_thrown",
+            entity: Some(e.to_bits()),
+            ingredient: name(&ids, a.0),
+            x: Some(throw.spawn_x),
+            y: Some(throw.spawn_y),
+            ..default()
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_events(
+    mut log: ResMut<RunLog>,
+    run_time: Res<RunTime>,
+    ids: Query<&Id>,
+    mut presses: EventReader<Process>,
+    mut hits: EventReader<ProcessIngredient>,
+    mut misses: EventReader<Miss>,
+    mut processed: EventReader<Processed>,
+    mut serves: EventReader<Serve>,
+    mut spawned: EventReader<OrderSpawned>,
+    mut steps: EventReader<StepCompleted>,
+    mut completed: EventReader<OrderCompleted>,
+    mut failed: EventReader<OrderFailed>,
+    mut strikes: EventReader<Strike>,
+    mut over: EventReader<GameOver>,
+) {
+    let t = run_time.0.elapsed_secs();
+    let entries = &mut log.entries;
+    for evt in presses.read() {
+        entries.push(LogEntry { t, event: "press", player: player(evt.2), tool: name(&ids, evt.0), x: Some(evt.1.x), y: Some(evt.1.y), ..default() });
+    }
+    for evt in hits.read() {
+        entries.push(LogEntry {
+            t,
+            event: "hit",
+            entity: Some(evt.active.to_bits()),
+            player: player(evt.player),
+            ingredient: name(&ids, evt.ingredient),
+            tool: name(&ids, evt.process),
+            x: Some(evt.at.x),
+            y: Some(evt.at.y),
+            detail: Some(format!("{:?}", evt.grade)),
+            ..default()
+        });
+    }
+    for evt in misses.read() {
+        entries.push(LogEntry { t, event: "miss", player: player(evt.2), tool: name(&ids, evt.0), x: Some(evt.1.x), y: Some(evt.1.y), ..default() });
+    }
+    for evt in processed.read() {
+        let (food, outcome) = match evt.outcome {
+            Outcome::Used(food) => (Some(food.to_bits()), "used"),
+            Outcome::Cooking(food) => (Some(food.to_bits()), "cooking"),
+            Outcome::Busy => (None, "busy"),
+            Outcome::Wasted => (None, "wasted"),
+        };
+        entries.push(LogEntry {
+            t,
+            event: "processed",
+            entity: food,
+            player: player(evt.player),
+            ingredient: name(&ids, evt.ingredient),
+            tool: name(&ids, evt.process),
+            detail: Some(outcome.to_string()),
+            ..default()
+        });
+    }
+    for evt in serves.read() {
+        entries.push(LogEntry { t, event: "serve", player: player(evt.1), tool: name(&ids, evt.0), ..default() });
+    }
+    for evt in spawned.read() {
+        entries.push(LogEntry {
+            t,
+            event: "order_spawned",
+            entity: Some(evt.food.to_bits()),
+            recipe: Some(evt.recipe.0.clone()),
+            remaining: Some(evt.steps),
+            ..default()
+        });
+    }
+    for evt in steps.read() {
+        entries.push(LogEntry {
+            t,
+            event: "step_completed",
+            entity: Some(evt.food.to_bits()),
+            player: player(evt.player),
+            recipe: Some(evt.recipe.0.clone()),
+            ingredient: Some(evt.ingredient.0.clone()),
+            tool: Some(evt.tool.0.clone()),
+            remaining: Some(evt.remaining),
+            since_spawn: Some(evt.since_spawn.as_secs_f32()),
+            detail: Some(format!("{:?}", evt.grade)),
+            ..default()
+        });
+    }
+    for evt in completed.read() {
+        entries.push(LogEntry {
+            t,
+            event: "order_completed",
+            entity: Some(evt.food.to_bits()),
+            player: player(evt.player),
+            recipe: Some(evt.recipe.0.clone()),
+            tool: Some(evt.tool.0.clone()),
+            remaining: Some(0),
+            since_spawn: Some(evt.since_spawn.as_secs_f32()),
+            detail: Some(format!("{:?}", evt.grades)),
+            ..default()
+        });
+    }
+    for evt in failed.read() {
+        entries.push(LogEntry {
+            t,
+            event: "order_failed",
+            entity: Some(evt.food.to_bits()),
+            recipe: Some(evt.recipe.0.clone()),
+            remaining: Some(evt.remaining),
+            since_spawn: Some(evt.since_spawn.as_secs_f32()),
+            ..default()
+        });
+    }
+    for evt in strikes.read() {
+        entries.push(LogEntry { t, event: "strike", detail: Some(format!("{:?}", evt.0)), ..default() });
+    }
+    for evt in over.read() {
+        entries.push(LogEntry { t, event: "game_over", detail: Some(format!("{:?}", evt.0)), ..default() });
+    }
+}
+
+fn csv_field<T: ToString>(value: &Option<T>) -> String {
+    let Some(value) = value else { return String::new() };
+    let value = value.to_string();
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn to_csv(entries: &[LogEntry]) -> String {
+    let mut out = String::from("t,event,entity,player,recipe,ingredient,tool,remaining,since_spawn,x,y,detail\n");
+    for e in entries {
+        out += &format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            e.t,
+            e.event,
+            csv_field(&e.entity),
+            csv_field(&e.player),
+            csv_field(&e.recipe),
+            csv_field(&e.ingredient),
+            csv_field(&e.tool),
+            csv_field(&e.remaining),
+            csv_field(&e.since_spawn),
+            csv_field(&e.x),
+            csv_field(&e.y),
+            csv_field(&e.detail),
+        );
+    }
+    out
+}
+
+fn to_jsonl(entries: &[LogEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|e| serde_json::to_string(e).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+fn path(started: &str, ext: &str) -> Option<PathBuf> {
+    Some(save::data_dir()?.join("runs").join(format!("run-{}.{}", started, ext)))
+}
+
+fn export_log(log: Res<RunLog>, cfg: Res<RunLogConfig>) {
+    let (text, ext) = match cfg.format {
+        LogFormat::Off => return,
+        LogFormat::Jsonl => (to_jsonl(&log.entries), "jsonl"),
+        LogFormat::Csv => (to_csv(&log.entries), "csv"),
+    };
+    let Some(path) = path(&log.started, ext) else { return };
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    match fs::write(&path, text) {
+        Ok(()) => info!("wrote run log to {:?}", path),
+        Err(e) => error!("failed to write run log to {:?}: {}", path, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field::<String>(&None), "");
+        assert_eq!(csv_field(&Some("pan")), "pan");
+        assert_eq!(csv_field(&Some("a,b")), "\"a,b\"");
+        assert_eq!(csv_field(&Some("say \"hi\"")), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field(&Some("two\nlines")), "\"two\nlines\"");
+    }
+}
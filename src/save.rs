@@ -0,0 +1,70 @@
+use std::{ fs, path::{ Path, PathBuf } };
+
+use bevy::prelude::*;
+use serde::{ de::DeserializeOwned, Serialize };
+
+pub fn data_dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("hungry_valentine"))
+}
+
+pub fn load<T: DeserializeOwned + Default>(file: &str) -> T {
+    let Some(path) = data_dir().map(|dir| dir.join(file)) else { return T::default() };
+    load_from(&path)
+}
+
+// missing files give the default, broken ones are moved aside so they don't get overwritten
+fn load_from<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let Ok(text) = fs::read_to_string(path) else { return T::default() };
+
+    match ron::from_str(&text) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("{:?} is corrupt ({}), starting over", path, e);
+            let _ = fs::rename(path, path.with_extension("ron.bak"));
+            T::default()
+        }
+    }
+}
+
+pub fn save<T: Serialize>(file: &str, value: &T) {
+    let Some(dir) = data_dir() else { return };
+    let path = dir.join(file);
+    let text = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("failed to serialize {:?}: {}", path, e);
+            return;
+        }
+    };
+    let _ = fs::create_dir_all(&dir);
+    if let Err(e) = fs::write(&path, text) {
+        error!("failed to save {:?}: {}", path, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_file_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("hungry_valentine_save_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scores.ron");
+        fs::write(&path, "([oops").unwrap();
+
+        let loaded: Vec<u32> = load_from(&path);
+        assert!(loaded.is_empty());
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(dir.join("scores.ron.bak")).unwrap(), "([oops");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_file_gives_default() {
+        let path = std::env::temp_dir().join("hungry_valentine_missing.ron");
+        let loaded: Vec<u32> = load_from(&path);
+        assert!(loaded.is_empty());
+    }
+}
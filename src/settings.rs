@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+use serde::{ Deserialize, Serialize };
+
+use crate::save;
+
+const FILE: &str = "settings.ron";
+
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub pause_on_focus_loss: bool,
+    pub fullscreen: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { pause_on_focus_loss: true, fullscreen: false }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        save::load(FILE)
+    }
+
+    pub fn save(&self) {
+        save::save(FILE, self);
+    }
+}
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use crate::{
+    key_label, spawn_order, Burnt, throw_ingredient, Active, Food, FoodIngredients, GameMode, GameState, Id, InRun, Ingredient, Ingredients, KeyMapping,
+    OrderCount, OrderSpawned, ProcessIngredient, Processing, RunState, Station, StepCompleted, Tex, Throw,
+};
+use crate::view;
+
+// the throw value is a speed, half the slowest regular throw so everything hangs in the air twice as long
+const TUTORIAL_THROW: f32 = 0.05;
+const THROW_XS: [f32; 3] = [-150., 0., 150.];
+// the tutorial never lets an order run out
+const TUTORIAL_PATIENCE: f32 = 3600.;
+
+pub struct TutorialPlugin;
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Tutorial>()
+            .add_systems(OnEnter(GameState::Game), tutorial_setup.run_if(resource_equals(GameMode::Tutorial)))
+            .add_systems(Update, (run_lesson, update_prompt).chain().run_if(in_state(GameState::Game)).run_if(in_state(RunState::Running)).run_if(resource_equals(GameMode::Tutorial)));
+    }
+}
+
+enum Lesson {
+    // put up an order and move straight on
+    Order(&'static str),
+    // keep throwing the ingredient until it gets hit with the tool
+    Process(&'static str, &'static str),
+    // wait for the station to be served
+    Serve(&'static str),
+}
+
+const LESSONS: [Lesson; 10] = [
+    Lesson::Order("orange_cut"),
+    Lesson::Process("orange", "knife"),
+    Lesson::Order("fried_egg"),
+    Lesson::Process("egg", "pan"),
+    Lesson::Serve("pan"),
+    Lesson::Order("sushi"),
+    Lesson::Process("nori", "knife"),
+    Lesson::Process("fish", "knife"),
+    Lesson::Process("rice", "pot"),
+    Lesson::Serve("pot"),
+];
+
+#[derive(Resource, Default)]
+struct Tutorial {
+    lesson: usize,
+    throws: usize,
+    hint: Option<String>,
+}
+
+#[derive(Component)]
+struct TutorialPrompt;
+
+fn key_for(keymap: &KeyMapping, tool: Entity) -> String {
+    key_label(keymap, tool).unwrap_or("?".to_string())
+}
+
+fn tutorial_setup(mut cmd: Commands, mut tutorial: ResMut<Tutorial>) {
+    *tutorial = Tutorial::default();
+    let (screen, area) = view::play_area();
+    cmd.spawn((screen, InRun)).with_children(|parent| {
+        parent.spawn(area).with_children(|parent| {
+            parent.spawn((
+                TutorialPrompt,
+                TextBundle::from_section("", TextStyle { font_size: 20.0, color: Color::WHITE, ..default() })
+                    .with_text_alignment(TextAlignment::Center)
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(90.0),
+                        left: Val::Px(20.0),
+                        right: Val::Px(20.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    }),
+            ));
+        });
+    });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn run_lesson(
+    mut cmd: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    mut hits: EventReader<ProcessIngredient>,
+    mut steps: EventReader<StepCompleted>,
+    mut burnt: EventReader<Burnt>,
+    mut spawned: EventWriter<OrderSpawned>,
+    mut count: ResMut<OrderCount>,
+    recipes: Query<(Entity, &Id, &Tex, &FoodIngredients), (With<Food>, Without<Active>)>,
+    tools: Query<(Entity, &Id, Option<&Station>), With<Processing>>,
+    tex: Query<&Tex, With<Ingredient>>,
+    ingredients: Res<Ingredients>,
+    flying: Query<&Active, With<Throw>>,
+    ids: Query<&Id>,
+) {
+    let Some(lesson) = LESSONS.get(tutorial.lesson) else { return };
+    match lesson {
+        Lesson::Order(recipe) => {
+            let Some((e, id, tex, steps)) = recipes.iter().find(|(_, id, ..)| id.0 == *recipe) else { return };
+            let food = spawn_order(&mut cmd, &mut count, e, tex, steps, TUTORIAL_PATIENCE);
+            spawned.send(OrderSpawned { food, recipe: id.clone(), steps: steps.0.len() });
+            tutorial.lesson += 1;
+        }
+        Lesson::Process(ingredient, tool) => {
+            let Some(&ingredient_e) = ingredients.0.get(&Id(ingredient.to_string())) else { return };
+            let Some((tool_e, ..)) = tools.iter().find(|(_, id, _)| id.0 == *tool) else { return };
+
+            for hit in hits.read() {
+                if hit.ingredient == ingredient_e && hit.process == tool_e {
+                    tutorial.lesson += 1;
+                    tutorial.hint = None;
+                    return;
+                }
+                let used = ids.get(hit.process).map(|id| id.0.as_str()).unwrap_or("?");
+                let on = ids.get(hit.ingredient).map(|id| id.0.as_str()).unwrap_or("?");
+                tutorial.hint = Some(format!("not quite, that was the {} on the {}", used, on));
+            }
+
+            if !flying.iter().any(|a| a.0 == ingredient_e) {
+                let x = THROW_XS[tutorial.throws % THROW_XS.len()];
+                tutorial.throws += 1;
+                throw_ingredient(&mut cmd, ingredient_e, tex.get(ingredient_e).unwrap(), x, 0., TUTORIAL_THROW);
+            }
+        }
+        Lesson::Serve(tool) => {
+            let Some((tool_e, ..)) = tools.iter().find(|(_, id, _)| id.0 == *tool) else { return };
+            if steps.read().any(|step| step.tool.0 == *tool) {
+                tutorial.lesson += 1;
+                tutorial.hint = None;
+            } else if burnt.read().any(|b| b.0 == tool_e) {
+                // it burnt, go back and cook it again
+                tutorial.lesson -= 1;
+                tutorial.hint = Some(format!("the {} burnt it, try again and serve it sooner", tool));
+            }
+        }
+    }
+    hits.clear();
+    steps.clear();
+    burnt.clear();
+}
+
+fn update_prompt(
+    tutorial: Res<Tutorial>,
+    keymap: Res<KeyMapping>,
+    tools: Query<(Entity, &Id, Option<&Station>), With<Processing>>,
+    mut prompt: Query<&mut Text, With<TutorialPrompt>>,
+) {
+    let Ok(mut prompt) = prompt.get_single_mut() else { return };
+    let tool = |name: &str| tools.iter().find(|(_, id, _)| id.0 == name);
+
+    let text = match LESSONS.get(tutorial.lesson) {
+        Some(Lesson::Process(ingredient, name)) => match tool(name) {
+            Some((e, _, Some(_))) => format!("press {} to put the {} in the {}", key_for(&keymap, e), ingredient, name),
+            Some((e, _, None)) => format!("press {} for the {} on the {}", key_for(&keymap, e), name, ingredient),
+            None => String::new(),
+        },
+        Some(Lesson::Serve(name)) => match tool(name) {
+            Some((e, ..)) => format!("wait until the {} is done, then press {} again to serve it", name, key_for(&keymap, e)),
+            None => String::new(),
+        },
+        Some(Lesson::Order(_)) => String::new(),
+        None => {
+            let toaster = tool("toaster").map(|(e, ..)| key_for(&keymap, e)).unwrap_or("?".to_string());
+            format!("that's the kitchen! bread goes in the toaster on {}.\npress Esc and quit to head back to the menu", toaster)
+        }
+    };
+    let text = match &tutorial.hint {
+        Some(hint) => format!("{}\n{}", hint, text),
+        None => text,
+    };
+    if prompt.sections[0].value != text {
+        prompt.sections[0].value = text;
+    }
+}
@@ -1,13 +1,66 @@
 use bevy::prelude::*;
-use crate::{ GameState, Score };
+use bevy::window::ReceivedCharacter;
+use crate::{ Active, Food, Id, Tex, ZenRecipes, GameState, GameMode, GameRng, Score, ScoreBreakdown, Combo, FailedOrders, Wasted, Lost, LoseCondition, LoseReason, Strikes, RunTime, PlayerStats,
+    Owner, FoodSpawnTimer, IngredientSpawnTimer, RunState, RecipesServed, RetrySeed, FoodIngredients, Ingredients, Processing };
+use crate::settings::Settings;
+use crate::highscore::{ Entry, HighScores };
+use crate::campaign::{ CampaignProgress, CurrentLevel, Levels };
+use crate::daily::{ Challenge, DailyResults };
+use crate::view;
 
 pub struct MenuPlugin;
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(OnEnter(GameState::MainMenu), main_menu_setup)
-            .add_systems(Update, (menu_action, button_system).run_if(in_state(GameState::MainMenu)))
-            .add_systems(OnExit(GameState::MainMenu), despawn_screen::<OnMainMenuScreen>);
+            .add_state::<MenuState>()
+            .add_systems(OnEnter(GameState::MainMenu), menu_setup)
+            .add_systems(OnEnter(MenuState::Main), main_menu_setup)
+            .add_systems(OnExit(MenuState::Main), despawn_screen::<OnMainMenuScreen>)
+            .add_systems(OnEnter(MenuState::Modes), modes_setup)
+            .add_systems(OnExit(MenuState::Modes), despawn_screen::<OnModesScreen>)
+            .add_systems(OnEnter(MenuState::TwoPlayer), two_player_setup)
+            .add_systems(OnExit(MenuState::TwoPlayer), despawn_screen::<OnTwoPlayerScreen>)
+            .add_systems(OnEnter(MenuState::Zen), zen_setup)
+            .add_systems(OnExit(MenuState::Zen), despawn_screen::<OnZenScreen>)
+            .add_systems(Update, (recipe_toggle, zen_start.run_if(resource_changed::<ZenRecipes>())).chain().run_if(in_state(MenuState::Zen)))
+            .add_systems(OnEnter(MenuState::Levels), levels_setup)
+            .add_systems(OnExit(MenuState::Levels), despawn_screen::<OnLevelsScreen>)
+            .add_systems(OnEnter(MenuState::HighScores), highscores_setup)
+            .add_systems(OnExit(MenuState::HighScores), despawn_screen::<OnHighScoresScreen>)
+            .add_systems(OnEnter(MenuState::RecipeBook), recipe_book_setup)
+            .add_systems(OnExit(MenuState::RecipeBook), despawn_screen::<OnRecipeBookScreen>)
+            .add_systems(Update, (menu_action, button_system, show_hint).run_if(
+                in_state(GameState::MainMenu).or_else(in_state(GameState::GameOver)).or_else(in_state(RunState::Paused))
+            ))
+            .add_systems(OnEnter(GameState::GameOver), game_over_setup)
+            .add_systems(OnExit(GameState::GameOver), despawn_screen::<OnGameOverScreen>)
+            .add_state::<PauseMenuState>()
+            .init_resource::<BookShortcut>()
+            .add_systems(OnEnter(RunState::Paused), enter_pause)
+            .add_systems(OnExit(RunState::Paused), |mut pause_state: ResMut<NextState<PauseMenuState>>| pause_state.set(PauseMenuState::Disabled))
+            .add_systems(OnEnter(PauseMenuState::Main), pause_setup)
+            .add_systems(OnExit(PauseMenuState::Main), despawn_screen::<OnPauseScreen>)
+            .add_systems(OnEnter(PauseMenuState::Settings), settings_setup)
+            .add_systems(OnExit(PauseMenuState::Settings), despawn_screen::<OnSettingsScreen>)
+            .add_systems(OnEnter(PauseMenuState::RecipeBook), recipe_book_setup)
+            .add_systems(OnExit(PauseMenuState::RecipeBook), despawn_screen::<OnRecipeBookScreen>)
+            .add_systems(Update, open_recipe_book.run_if(in_state(GameState::Game)).run_if(in_state(RunState::Running)))
+            .add_systems(Update, resume_on_esc.run_if(in_state(PauseMenuState::Main)))
+            .add_systems(Update, close_recipe_book.run_if(in_state(PauseMenuState::RecipeBook)))
+            .add_systems(Update, settings_labels.run_if(in_state(PauseMenuState::Settings)))
+            .add_systems(OnEnter(GameState::NameEntry), name_entry_setup)
+            .add_systems(Update, name_entry.run_if(in_state(GameState::NameEntry)))
+            .add_systems(OnExit(GameState::NameEntry), despawn_screen::<OnNameEntryScreen>);
+    }
+}
+
+pub struct HudPlugin;
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::Game), hud_setup)
+            .add_systems(Update, update_hud.run_if(in_state(GameState::Game)))
+            .add_systems(OnExit(GameState::Game), despawn_screen::<OnHud>);
     }
 }
 
@@ -17,12 +70,114 @@ fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands
     }
 }
 
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+enum MenuState {
+    Main,
+    Modes,
+    TwoPlayer,
+    Zen,
+    Levels,
+    HighScores,
+    RecipeBook,
+    #[default]
+    Disabled,
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+enum PauseMenuState {
+    Main,
+    Settings,
+    RecipeBook,
+    #[default]
+    Disabled,
+}
+
 #[derive(Component)]
 struct OnMainMenuScreen;
 
+#[derive(Component)]
+struct OnPauseScreen;
+
+#[derive(Component)]
+struct OnGameOverScreen;
+
+#[derive(Component)]
+struct OnSettingsScreen;
+
+#[derive(Component)]
+struct OnModesScreen;
+
+#[derive(Component)]
+struct OnTwoPlayerScreen;
+
+#[derive(Component)]
+struct Hint(String);
+
+#[derive(Component)]
+struct HintText;
+
+#[derive(Component)]
+struct OnZenScreen;
+
+#[derive(Component)]
+struct ZenStart;
+
+#[derive(Component)]
+struct RecipeToggle(Id);
+
+#[derive(Component)]
+struct OnLevelsScreen;
+
+#[derive(Component)]
+struct OnHighScoresScreen;
+
+#[derive(Component)]
+struct OnRecipeBookScreen;
+
+// the recipe book key pauses straight into the book instead of the pause menu
+#[derive(Resource, Default)]
+struct BookShortcut(bool);
+
+#[derive(Component)]
+struct OnNameEntryScreen;
+
+#[derive(Component)]
+struct NameInput(String);
+
+#[derive(Component)]
+struct OnHud;
+
+#[derive(Component, Clone, Copy)]
+enum HudText {
+    Score,
+    Combo,
+    Speed,
+    Room,
+    Lives,
+    Time,
+    Players,
+}
+
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
+    Start(GameMode),
+    ZenSetup,
+    Campaign,
+    StartLevel(usize),
+    TwoPlayer,
+    Resume,
+    Restart,
+    Settings,
+    TogglePauseOnFocusLoss,
+    ToggleFullscreen,
+    BackToPause,
+    QuitToMenu,
+    Retry,
+    HighScores,
+    RecipeBook,
+    PauseRecipeBook,
+    BackToMainMenu,
     Quit,
 }
 
@@ -31,13 +186,18 @@ const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.25, 0.65, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const MAX_NAME_LEN: usize = 12;
+const BOOK_KEY: KeyCode = KeyCode::Tab;
 
-fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, score: Res<Score>) {
+fn main_menu_setup(
+    mut commands: Commands,
+    score: Res<Score>,
+) {
     // Common style for all buttons on the screen
     let button_style = Style {
         width: Val::Px(250.0),
-        height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(10.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -93,7 +253,7 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, score
                             },
                         )
                         .with_style(Style {
-                            margin: UiRect::all(Val::Px(50.0)),
+                            margin: UiRect::all(Val::Px(30.0)),
                             ..default()
                         }),
                     );
@@ -136,6 +296,44 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, score
                                 button_text_style.clone(),
                             ));
                         });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::HighScores,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Scores",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::RecipeBook,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Recipes",
+                                button_text_style.clone(),
+                            ));
+                        });
                     parent
                         .spawn((
                             ButtonBundle {
@@ -156,27 +354,1215 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, score
         });
 }
 
-fn menu_action(
-    interaction_query: Query<
-        (&Interaction, &MenuButtonAction),
-        (Changed<Interaction>, With<Button>),
-    >,
-    //mut menu_state: ResMut<NextState<MenuState>>,
-    mut game_state: ResMut<NextState<GameState>>,
+fn resume_on_esc(keys: Res<Input<KeyCode>>, mut run_state: ResMut<NextState<RunState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        run_state.set(RunState::Running);
+    }
+}
+
+fn enter_pause(mut shortcut: ResMut<BookShortcut>, mut pause_state: ResMut<NextState<PauseMenuState>>) {
+    pause_state.set(if shortcut.0 { PauseMenuState::RecipeBook } else { PauseMenuState::Main });
+    shortcut.0 = false;
+}
+
+fn open_recipe_book(keys: Res<Input<KeyCode>>, mut shortcut: ResMut<BookShortcut>, mut run_state: ResMut<NextState<RunState>>) {
+    if keys.just_pressed(BOOK_KEY) {
+        shortcut.0 = true;
+        run_state.set(RunState::Paused);
+    }
+}
+
+// the same key closes the book and goes straight back to the kitchen
+fn close_recipe_book(keys: Res<Input<KeyCode>>, mut run_state: ResMut<NextState<RunState>>) {
+    if keys.any_just_pressed([BOOK_KEY, KeyCode::Escape]) {
+        run_state.set(RunState::Running);
+    }
+}
+
+fn pause_setup(commands: Commands, mode: Res<GameMode>) {
+    overlay(commands, OnPauseScreen, "paused", vec![
+        (Some(MenuButtonAction::Resume), "Resume".to_string()),
+        // no second tries on the daily
+        ((*mode != GameMode::Daily).then_some(MenuButtonAction::Restart), "Restart".to_string()),
+        (Some(MenuButtonAction::PauseRecipeBook), "Recipes".to_string()),
+        (Some(MenuButtonAction::Settings), "Settings".to_string()),
+        (Some(MenuButtonAction::QuitToMenu), "Quit to menu".to_string()),
+    ]);
+}
+
+fn focus_label(settings: &Settings) -> String {
+    format!("pause on focus loss: {}", if settings.pause_on_focus_loss { "on" } else { "off" })
+}
+
+fn fullscreen_label(settings: &Settings) -> String {
+    format!("fullscreen (F11): {}", if settings.fullscreen { "on" } else { "off" })
+}
+
+fn settings_setup(commands: Commands, settings: Res<Settings>) {
+    overlay(commands, OnSettingsScreen, "settings", vec![
+        (Some(MenuButtonAction::TogglePauseOnFocusLoss), focus_label(&settings)),
+        (Some(MenuButtonAction::ToggleFullscreen), fullscreen_label(&settings)),
+        (Some(MenuButtonAction::BackToPause), "Back".to_string()),
+    ]);
+}
+
+fn settings_labels(settings: Res<Settings>, buttons: Query<(&MenuButtonAction, &Children)>, mut texts: Query<&mut Text>) {
+    if !settings.is_changed() { return; }
+    for (action, children) in buttons.iter() {
+        let label = match action {
+            MenuButtonAction::TogglePauseOnFocusLoss => focus_label(&settings),
+            MenuButtonAction::ToggleFullscreen => fullscreen_label(&settings),
+            _ => continue
+        };
+        for child in children.iter() {
+            let Ok(mut text) = texts.get_mut(*child) else { continue };
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+// a dimmed layer over the running game with a title and a column of buttons
+fn overlay<T: Component>(mut commands: Commands, screen: T, title: &str, entries: Vec<(Option<MenuButtonAction>, String)>) {
+    let button_style = Style {
+        width: Val::Px(300.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 24.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                // above the hud
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            screen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    title,
+                    TextStyle {
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                }),
+            );
+            for (action, label) in entries {
+                let text_style = match action {
+                    Some(_) => button_text_style.clone(),
+                    None => TextStyle { color: Color::GRAY, ..button_text_style.clone() },
+                };
+                let mut button = parent.spawn(ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                });
+                match action {
+                    Some(action) => button.insert(action),
+                    None => button.remove::<Button>(),
+                };
+                button.with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(label, text_style));
+                });
+            }
+        });
+}
+
+fn player_summary(mode: &GameMode, stats: &PlayerStats) -> String {
+    if mode.players() < 2 { return String::new(); }
+    stats.0.iter().enumerate().map(|(i, p)| {
+        format!("\np{}: {} points, served {}, hits {}, misses {}, wasted {}", i + 1, p.score, p.served, p.hits, p.misses, p.wasted)
+    }).collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn game_over_setup(
+    mut commands: Commands,
+    score: Res<Score>,
+    breakdown: Res<ScoreBreakdown>,
+    combo: Res<Combo>,
+    failed: Res<FailedOrders>,
+    wasted: Res<Wasted>,
+    mode: Res<GameMode>,
+    lost: Res<Lost>,
+    stats: Res<PlayerStats>,
+    recipes: Res<RecipesServed>,
+    run_time: Res<RunTime>,
 ) {
-    for (interaction, menu_button_action) in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            match menu_button_action {
-                MenuButtonAction::Quit => std::process::exit(0),
-                MenuButtonAction::Play => {
+    let text_style = TextStyle {
+        font_size: 18.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let button_style = Style {
+        width: Val::Px(150.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 32.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    let hits: usize = stats.0.iter().map(|p| p.hits).sum();
+    let misses: usize = stats.0.iter().map(|p| p.misses).sum();
+    let accuracy = (hits * 100).checked_div(hits + misses).unwrap_or(0);
+    let mut served: Vec<_> = recipes.0.iter().map(|(id, n)| format!("{} x{}", id.0, n)).collect();
+    served.sort();
+    let secs = run_time.0.elapsed_secs() as usize;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    if let Some(reason) = lost.0 {
+                        let title = match (*mode, reason) {
+                            (GameMode::Shift, _) => "the bell rang, shift over".to_string(),
+                            (_, LoseReason::LevelCleared | LoseReason::Overrun(_)) => reason.describe().to_string(),
+                            _ => "game over: ".to_string() + reason.describe(),
+                        };
+                        parent.spawn(
+                            TextBundle::from_section(
+                                format!("{}\nserved: {}  failed: {}  wasted: {}", title, breakdown.served, failed.0, wasted.0),
+                                TextStyle {
+                                    font_size: 24.0,
+                                    color: TEXT_COLOR,
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::horizontal(Val::Px(20.0)),
+                                ..default()
+                            }),
+                        );
+
+                        parent.spawn(
+                            TextBundle::from_section(
+                                format!(
+                                    "recipes: {}  grades: +{}  combo: +{} (best x{})\nspeed: +{}  penalties: -{}",
+                                    breakdown.base,
+                                    breakdown.grade,
+                                    breakdown.combo,
+                                    combo.best,
+                                    breakdown.speed,
+                                    breakdown.penalty,
+                                ) + &player_summary(&mode, &stats),
+                                TextStyle {
+                                    font_size: 18.0,
+                                    color: TEXT_COLOR,
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            }),
+                        );
+                    }
+
+
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "score: ".to_string() + &score.0.to_string(),
+                            TextStyle {
+                                font_size: 32.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+
+                    for line in [
+                        if served.is_empty() { "nothing served".to_string() } else { served.join("  ") },
+                        format!("hits: {}  misses: {}  accuracy: {}%", hits, misses, accuracy),
+                        format!("longest combo: {}  time: {}:{:02}", combo.best, secs / 60, secs % 60),
+                    ] {
+                        parent.spawn(TextBundle::from_section(line, text_style.clone()));
+                    }
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                margin: UiRect::top(Val::Px(10.0)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            // the daily only gets one go
+                            for (action, label) in [
+                                ((*mode != GameMode::Daily).then_some(MenuButtonAction::Retry), "Retry"),
+                                (Some(MenuButtonAction::QuitToMenu), "Menu"),
+                            ] {
+                                let text_style = match action {
+                                    Some(_) => button_text_style.clone(),
+                                    None => TextStyle { color: Color::GRAY, ..button_text_style.clone() },
+                                };
+                                let mut button = parent.spawn(ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                });
+                                match action {
+                                    Some(action) => button.insert(action),
+                                    None => button.remove::<Button>(),
+                                };
+                                button.with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(label, text_style));
+                                });
+                            }
+                        });
+                });
+        });
+}
+
+fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Main);
+}
+
+fn modes_setup(commands: Commands, daily: Res<DailyResults>) {
+    let challenge = Challenge::today();
+    let played = daily.get(&challenge.date);
+    let daily_hint = match played {
+        Some(result) => format!("played today: {} points, come back tomorrow", result.score),
+        None => format!("today: {}", challenge.describe()),
+    };
+
+    mode_list(commands, OnModesScreen, vec![
+        (Some(MenuButtonAction::Start(GameMode::Tutorial)), "Tutorial", "learn the keys and tools step by step".to_string()),
+        (Some(MenuButtonAction::Start(GameMode::Endless)), "Endless", "keep going until the kitchen is overrun".to_string()),
+        (Some(MenuButtonAction::Start(GameMode::Shift)), "Shift", "score as much as you can in 3 minutes".to_string()),
+        (Some(MenuButtonAction::ZenSetup), "Zen", "no pressure, pick the recipes you want to practice".to_string()),
+        (Some(MenuButtonAction::Campaign), "Campaign", "learn the menu one recipe at a time".to_string()),
+        // one attempt a day, after that the button stays dead
+        (played.is_none().then_some(MenuButtonAction::Start(GameMode::Daily)), "Daily", daily_hint),
+        (Some(MenuButtonAction::TwoPlayer), "2 Players", "share the kitchen with a friend".to_string()),
+        (Some(MenuButtonAction::BackToMainMenu), "Back", String::new()),
+    ]);
+}
+
+fn two_player_setup(commands: Commands) {
+    mode_list(commands, OnTwoPlayerScreen, vec![
+        (
+            Some(MenuButtonAction::Start(GameMode::Coop)),
+            "Co-op",
+            "player one: mouse and 1-4\nplayer two: move with IJKL and use 7-0, or a gamepad".to_string(),
+        ),
+        (
+            Some(MenuButtonAction::Start(GameMode::Versus)),
+            "Versus",
+            "same controls, but you each get your own orders\nevery dish you finish rushes an order to the other side".to_string(),
+        ),
+        (Some(MenuButtonAction::Play), "Back", String::new()),
+    ]);
+}
+
+// a column of buttons with one line underneath describing whatever is hovered
+fn mode_list<T: Component>(mut commands: Commands, screen: T, entries: Vec<(Option<MenuButtonAction>, &str, String)>) {
+    let button_style = Style {
+        width: Val::Px(250.0),
+        height: Val::Px(40.0),
+        margin: UiRect::all(Val::Px(3.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 32.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let hint_style = TextStyle {
+        font_size: 16.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            screen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (action, label, hint) in entries {
+                        let text_style = match action {
+                            Some(_) => button_text_style.clone(),
+                            None => TextStyle { color: Color::GRAY, ..button_text_style.clone() },
+                        };
+                        let mut button = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            Hint(hint),
+                        ));
+                        match action {
+                            Some(action) => button.insert(action),
+                            None => button.remove::<Button>(),
+                        };
+                        button.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, text_style));
+                        });
+                    }
+                    parent.spawn((
+                        TextBundle::from_section("", hint_style)
+                            .with_text_alignment(TextAlignment::Center)
+                            .with_style(Style {
+                                min_height: Val::Px(40.0),
+                                margin: UiRect::top(Val::Px(10.0)),
+                                ..default()
+                            }),
+                        HintText,
+                    ));
+                });
+        });
+}
+
+fn show_hint(buttons: Query<(&Interaction, &Hint)>, mut text: Query<&mut Text, With<HintText>>) {
+    let Ok(mut text) = text.get_single_mut() else { return };
+    let hint = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction != Interaction::None)
+        .map(|(_, hint)| hint.0.as_str())
+        .unwrap_or("");
+    if text.sections[0].value != hint {
+        text.sections[0].value = hint.to_string();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn zen_setup(
+    mut commands: Commands,
+    recipes: Query<(&Id, &Tex), (With<Food>, Without<Active>)>,
+    mut zen: ResMut<ZenRecipes>,
+) {
+    if zen.0.is_empty() {
+        zen.0 = recipes.iter().map(|(id, _)| id.clone()).collect();
+    }
+
+    let button_style = Style {
+        width: Val::Px(250.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnZenScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "pick the recipes to practice",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: TEXT_COLOR,
+                            ..default()
+                        },
+                    ));
+
+                    // one toggle per recipe
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (id, tex) in recipes.iter() {
+                                let selected = zen.0.contains(id);
+                                let mut button = parent.spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            width: Val::Px(64.0),
+                                            height: Val::Px(64.0),
+                                            margin: UiRect::all(Val::Px(4.0)),
+                                            padding: UiRect::all(Val::Px(4.0)),
+                                            ..default()
+                                        },
+                                        background_color: if selected { PRESSED_BUTTON } else { NORMAL_BUTTON }.into(),
+                                        ..default()
+                                    },
+                                    RecipeToggle(id.clone()),
+                                ));
+                                if selected {
+                                    button.insert(SelectedOption);
+                                }
+                                button.with_children(|parent| {
+                                    parent.spawn(ImageBundle {
+                                        style: Style {
+                                            width: Val::Percent(100.0),
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        image: UiImage::new(tex.0.clone()),
+                                        ..default()
+                                    });
+                                });
+                            }
+                        });
+
+                    for (action, label) in [
+                        (MenuButtonAction::Start(GameMode::Zen), "Start"),
+                        (MenuButtonAction::Play, "Back"),
+                    ] {
+                        let start = matches!(action, MenuButtonAction::Start(_));
+                        let mut button = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            action,
+                        ));
+                        if start {
+                            button.insert(ZenStart);
+                        }
+                        button.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(label, button_text_style.clone()));
+                        });
+                    }
+                });
+        });
+}
+
+fn recipe_toggle(
+    mut commands: Commands,
+    interaction_query: Query<(Entity, &Interaction, &RecipeToggle, Option<&SelectedOption>), Changed<Interaction>>,
+    mut zen: ResMut<ZenRecipes>,
+) {
+    for (entity, interaction, toggle, selected) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if selected.is_some() {
+            zen.0.remove(&toggle.0);
+            commands.entity(entity).remove::<SelectedOption>();
+        } else {
+            zen.0.insert(toggle.0.clone());
+            commands.entity(entity).insert(SelectedOption);
+        }
+    }
+}
+
+// with nothing picked the filter would let every recipe through, so start only works with at least one
+fn zen_start(
+    mut commands: Commands,
+    zen: Res<ZenRecipes>,
+    mut start: Query<(Entity, &Children, &mut BackgroundColor), With<ZenStart>>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok((entity, children, mut color)) = start.get_single_mut() else { return };
+    let enabled = !zen.0.is_empty();
+    if enabled {
+        commands.entity(entity).insert(Button);
+    } else {
+        commands.entity(entity).remove::<Button>();
+        *color = NORMAL_BUTTON.into();
+    }
+    for child in children.iter() {
+        let Ok(mut text) = texts.get_mut(*child) else { continue };
+        text.sections[0].style.color = if enabled { TEXT_COLOR } else { Color::GRAY };
+    }
+}
+
+fn levels_setup(mut commands: Commands, levels: Res<Levels>, progress: Res<CampaignProgress>) {
+    let button_style = Style {
+        width: Val::Px(400.0),
+        height: Val::Px(40.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let level_style = TextStyle {
+        font_size: 20.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnLevelsScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "campaign",
+                            TextStyle {
+                                font_size: 24.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+
+                    if levels.0.is_empty() {
+                        parent.spawn(TextBundle::from_section("no levels found", level_style.clone()));
+                    }
+                    for (i, level) in levels.0.iter().enumerate() {
+                        let label = match progress.best(i) {
+                            Some(best) => format!("{}. {}  {}/{}", i + 1, level.name, best, level.target),
+                            None => format!("{}. {}  goal {}", i + 1, level.name, level.target),
+                        };
+                        // locked levels are plain boxes without an action
+                        let mut button = parent.spawn(ButtonBundle {
+                            style: button_style.clone(),
+                            background_color: NORMAL_BUTTON.into(),
+                            ..default()
+                        });
+                        if i < progress.unlocked {
+                            button.insert(MenuButtonAction::StartLevel(i));
+                            button.with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, level_style.clone()));
+                            });
+                        } else {
+                            button.remove::<Button>().with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    format!("{}. locked", i + 1),
+                                    TextStyle { color: Color::GRAY, ..level_style.clone() },
+                                ));
+                            });
+                        }
+                    }
+
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Play,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Back", level_style.clone()));
+                        });
+                });
+        });
+}
+
+// built from the recipe templates, so anything added to the menu shows up here too
+#[allow(clippy::type_complexity)]
+fn recipe_book_setup(
+    mut commands: Commands,
+    game_state: Res<State<GameState>>,
+    recipes: Query<(&Id, &Tex, &FoodIngredients), (With<Food>, Without<Active>)>,
+    ingredients: Res<Ingredients>,
+    tools: Query<(&Id, &Tex), With<Processing>>,
+    tex: Query<&Tex>,
+) {
+    let back = match game_state.get() {
+        GameState::Game => MenuButtonAction::BackToPause,
+        _ => MenuButtonAction::BackToMainMenu,
+    };
+    let text_style = TextStyle {
+        font_size: 18.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let icon = |handle: Handle<Image>, size: f32| ImageBundle {
+        style: Style {
+            width: Val::Px(size),
+            height: Val::Px(size),
+            ..default()
+        },
+        image: UiImage::new(handle),
+        ..default()
+    };
+
+    let mut recipes: Vec<_> = recipes.iter().collect();
+    recipes.sort_by_key(|(id, ..)| id.0.clone());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                // above the hud
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            OnRecipeBookScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "recipes",
+                    TextStyle {
+                        font_size: 32.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+
+            // one row per dish: the dish, its name, then every step as ingredient and tool
+            for (id, dish, steps) in recipes {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(560.0),
+                            align_items: AlignItems::Center,
+                            margin: UiRect::vertical(Val::Px(3.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(icon(dish.0.clone(), 40.0));
+                        parent.spawn(
+                            TextBundle::from_section(id.0.replace('_', " "), text_style.clone())
+                                .with_style(Style {
+                                    width: Val::Px(120.0),
+                                    margin: UiRect::horizontal(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                        );
+                        for step in steps.0.iter() {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        align_items: AlignItems::Center,
+                                        margin: UiRect::right(Val::Px(8.0)),
+                                        padding: UiRect::all(Val::Px(2.0)),
+                                        ..default()
+                                    },
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    if let Some(tex) = ingredients.0.get(&step.ingredient).and_then(|e| tex.get(*e).ok()) {
+                                        parent.spawn(icon(tex.0.clone(), 28.0));
+                                    }
+                                    if let Some((_, tex)) = tools.iter().find(|(id, _)| **id == step.processing) {
+                                        parent.spawn(icon(tex.0.clone(), 20.0));
+                                    }
+                                });
+                        }
+                    });
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(150.0),
+                            height: Val::Px(50.0),
+                            margin: UiRect::top(Val::Px(10.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Back",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: TEXT_COLOR,
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn highscores_setup(mut commands: Commands, highscores: Res<HighScores>) {
+    let entry_style = TextStyle {
+        font_size: 16.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnHighScoresScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "high scores",
+                            TextStyle {
+                                font_size: 24.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+
+                    if highscores.0.is_empty() {
+                        parent.spawn(TextBundle::from_section("no scores yet", entry_style.clone()));
+                    }
+                    for (i, entry) in highscores.0.iter().enumerate() {
+                        parent.spawn(TextBundle::from_section(
+                            format!("{:>2}. {:<12} {:>6}  {}  {}", i + 1, entry.name, entry.score, entry.date, entry.mode),
+                            entry_style.clone(),
+                        ));
+                    }
+
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(200.0),
+                                    height: Val::Px(50.0),
+                                    margin: UiRect::top(Val::Px(20.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::BackToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Back",
+                                TextStyle {
+                                    font_size: 40.0,
+                                    color: TEXT_COLOR,
+                                    ..default()
+                                },
+                            ));
+                        });
+                });
+        });
+}
+
+fn name_entry_setup(mut commands: Commands, score: Res<Score>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnNameEntryScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(30.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!("new high score: {}\ntype your name and press enter", score.0),
+                        TextStyle {
+                            font_size: 24.0,
+                            color: TEXT_COLOR,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "_",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::top(Val::Px(20.0)),
+                            ..default()
+                        }),
+                        NameInput(String::new()),
+                    ));
+                });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn name_entry(
+    mut chars: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut input: Query<(&mut NameInput, &mut Text)>,
+    mut highscores: ResMut<HighScores>,
+    score: Res<Score>,
+    rng: Res<GameRng>,
+    mode: Res<GameMode>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let (mut name, mut text) = input.single_mut();
+    for evt in chars.read() {
+        if !evt.char.is_control() && name.0.chars().count() < MAX_NAME_LEN {
+            name.0.push(evt.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        name.0.pop();
+    }
+    text.sections[0].value = name.0.clone() + "_";
+
+    if keys.just_pressed(KeyCode::Return) {
+        let name = name.0.trim();
+        highscores.insert(Entry {
+            name: if name.is_empty() { "anon".to_string() } else { name.to_string() },
+            score: score.0,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            seed: rng.seed,
+            mode: mode.name().to_string(),
+        });
+        highscores.save();
+        game_state.set(GameState::GameOver);
+    }
+}
+
+fn hud_setup(mut commands: Commands) {
+    let big = TextStyle {
+        font_size: 24.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let small = TextStyle {
+        font_size: 16.0,
+        ..big.clone()
+    };
+
+    let (screen, area) = view::play_area();
+    commands
+        .spawn((screen, OnHud))
+        .with_children(|parent| {
+            parent.spawn(area).with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(10.0),
+                            right: Val::Px(10.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::End,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (field, style) in [
+                            (HudText::Score, &big),
+                            (HudText::Combo, &small),
+                            (HudText::Speed, &small),
+                            (HudText::Room, &small),
+                            (HudText::Lives, &big),
+                            (HudText::Time, &big),
+                            (HudText::Players, &big),
+                        ] {
+                            parent.spawn((TextBundle::from_section("", style.clone()), field));
+                        }
+                    });
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_hud(
+    score: Res<Score>,
+    combo: Res<Combo>,
+    food_timer: Res<FoodSpawnTimer>,
+    ingredient_timer: Res<IngredientSpawnTimer>,
+    cond: Res<LoseCondition>,
+    strikes: Res<Strikes>,
+    run_time: Res<RunTime>,
+    mode: Res<GameMode>,
+    stats: Res<PlayerStats>,
+    orders: Query<Option<&Owner>, (With<Food>, With<Active>)>,
+    mut texts: Query<(&mut Text, &HudText)>,
+) {
+    let mut queues = [0; 2];
+    for owner in orders.iter() {
+        queues[owner.map_or(0, |o| o.0.index())] += 1;
+    }
+
+    for (mut text, field) in texts.iter_mut() {
+        let value = match field {
+            HudText::Score => format!("score: {}", score.0),
+            HudText::Combo if combo.current > 1 => format!("combo {} (x{:.1})", combo.current, combo.multiplier()),
+            HudText::Combo => String::new(),
+            HudText::Speed => format!(
+                "orders every {:.1}s, ingredients every {:.1}s",
+                food_timer.0.duration().as_secs_f32(),
+                ingredient_timer.0.duration().as_secs_f32(),
+            ),
+            // the run ends on the order after the last free spot
+            HudText::Room => match (cond.max_queue, mode.split_queues()) {
+                (Some(max), true) => format!("room: p1 {}  p2 {}", max.saturating_sub(queues[0]), max.saturating_sub(queues[1])),
+                (Some(max), false) => format!("room for {} more orders", max.saturating_sub(queues[0])),
+                (None, _) => String::new(),
+            },
+            HudText::Lives => match cond.lives(&strikes) {
+                Some(lives) => "lives: ".to_string() + &lives.to_string(),
+                None => String::new(),
+            },
+            HudText::Time => match cond.time_left(&run_time) {
+                Some(left) => format!("time: {:.0}", left.ceil()),
+                None => String::new(),
+            },
+            HudText::Players => match mode.players() {
+                2 => format!("p1: {}  p2: {}", stats.0[0].score, stats.0[1].score),
+                _ => String::new(),
+            },
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn menu_action(
+    interaction_query: Query<
+        (&Interaction, &MenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut game_mode: ResMut<GameMode>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut run_state: ResMut<NextState<RunState>>,
+    mut pause_state: ResMut<NextState<PauseMenuState>>,
+    mut settings: ResMut<Settings>,
+    mut retry: ResMut<RetrySeed>,
+    rng: Res<GameRng>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match menu_button_action {
+                MenuButtonAction::Quit => std::process::exit(0),
+                MenuButtonAction::Play => menu_state.set(MenuState::Modes),
+                MenuButtonAction::Start(mode) => {
+                    *game_mode = *mode;
+                    game_state.set(GameState::Game);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::ZenSetup => menu_state.set(MenuState::Zen),
+                MenuButtonAction::Campaign => menu_state.set(MenuState::Levels),
+                MenuButtonAction::TwoPlayer => menu_state.set(MenuState::TwoPlayer),
+                MenuButtonAction::Resume => run_state.set(RunState::Running),
+                MenuButtonAction::Restart => {
+                    run_state.set(RunState::Running);
+                    game_state.set(GameState::Restart);
+                }
+                MenuButtonAction::Settings => pause_state.set(PauseMenuState::Settings),
+                MenuButtonAction::TogglePauseOnFocusLoss => {
+                    settings.pause_on_focus_loss = !settings.pause_on_focus_loss;
+                    settings.save();
+                }
+                MenuButtonAction::ToggleFullscreen => {
+                    settings.fullscreen = !settings.fullscreen;
+                    settings.save();
+                }
+                MenuButtonAction::BackToPause => pause_state.set(PauseMenuState::Main),
+                MenuButtonAction::Retry => {
+                    retry.0 = Some(rng.seed);
+                    game_state.set(GameState::Game);
+                }
+                MenuButtonAction::QuitToMenu => {
+                    run_state.set(RunState::Running);
+                    game_state.set(GameState::MainMenu);
+                }
+                MenuButtonAction::StartLevel(level) => {
+                    current_level.0 = *level;
+                    *game_mode = GameMode::Campaign;
                     game_state.set(GameState::Game);
-                    //menu_state.set(MenuState::Disabled);
+                    menu_state.set(MenuState::Disabled);
                 }
+                MenuButtonAction::HighScores => menu_state.set(MenuState::HighScores),
+                MenuButtonAction::RecipeBook => menu_state.set(MenuState::RecipeBook),
+                MenuButtonAction::PauseRecipeBook => pause_state.set(PauseMenuState::RecipeBook),
+                MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
             }
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn button_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
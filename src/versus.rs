@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+    spawn_order, Active, Food, FoodIngredients, GameMode, GameRng, GameState, Id, OrderCompleted, OrderCount, OrderFailed, OrderSpawned, Owner, PatienceConfig,
+    Player, RecipeFilter, Tex,
+};
+
+const RUSH_COLOR: Color = Color::rgb(1., 0.6, 0.6);
+// rush orders run out twice as fast
+const RUSH_PATIENCE: f32 = 0.5;
+
+pub struct VersusPlugin;
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Rushes>()
+            .add_systems(OnEnter(GameState::Game), |mut rushes: ResMut<Rushes>| rushes.0.clear())
+            .add_systems(Update, send_rush.run_if(in_state(GameState::Game)).run_if(resource_equals(GameMode::Versus)));
+    }
+}
+
+// rush orders still on the board
+#[derive(Resource, Default)]
+struct Rushes(HashSet<Entity>);
+
+impl Player {
+    fn opponent(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
+
+// every finished order dumps an impatient extra order on the other player,
+// finishing a rush order doesn't send one back so they can't ping-pong forever
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn send_rush(
+    mut cmd: Commands,
+    mut completed: EventReader<OrderCompleted>,
+    mut failed: EventReader<OrderFailed>,
+    mut spawned: EventWriter<OrderSpawned>,
+    mut rushes: ResMut<Rushes>,
+    recipes: Query<(Entity, &Id, &Tex, &FoodIngredients), (With<Food>, Without<Active>)>,
+    filter: Res<RecipeFilter>,
+    patience: Res<PatienceConfig>,
+    mut rng: ResMut<GameRng>,
+    mut count: ResMut<OrderCount>,
+) {
+    for evt in failed.read() {
+        rushes.0.remove(&evt.food);
+    }
+    for evt in completed.read() {
+        if rushes.0.remove(&evt.food) { continue; }
+        // rushes follow whoever finishes what, so they come out of the throw stream and leave the order queue alone
+        let mut pool: Vec<_> = recipes.iter().filter(|(_, id, ..)| filter.allows(id)).collect();
+        pool.sort_by_key(|(_, id, ..)| id.0.clone());
+        let Some(&(e, id, tex, ingredients)) = pool.choose(&mut rng.throws) else { continue };
+
+        let time = (patience.base + patience.per_step * ingredients.0.len() as f32) * RUSH_PATIENCE;
+        let food = spawn_order(&mut cmd, &mut count, e, tex, ingredients, time);
+        cmd.entity(food).insert((Owner(evt.player.opponent()), Sprite { color: RUSH_COLOR, ..default() }));
+        rushes.0.insert(food);
+        spawned.send(OrderSpawned { food, recipe: id.clone(), steps: ingredients.0.len() });
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::{ prelude::*, render::camera::ScalingMode, window::{ PrimaryWindow, WindowMode } };
+
+use crate::settings::Settings;
+use crate::VIEW;
+
+const FULLSCREEN_KEY: KeyCode = KeyCode::F11;
+// wide enough to cover the rest of any window
+const BAR_SIZE: f32 = 10000.;
+
+pub struct ViewPlugin;
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, spawn_letterbox)
+            .add_systems(Update, (fit_view, toggle_fullscreen, apply_fullscreen.run_if(resource_changed::<Settings>())));
+    }
+}
+
+#[derive(Component)]
+struct Letterbox;
+
+// black bars around the play area so nothing outside of it shows on a wider or taller window
+fn spawn_letterbox(mut cmd: Commands) {
+    let half = (VIEW + BAR_SIZE) / 2.;
+    for at in [Vec2::new(-half.x, 0.), Vec2::new(half.x, 0.), Vec2::new(0., -half.y), Vec2::new(0., half.y)] {
+        cmd.spawn((
+            Letterbox,
+            SpriteBundle {
+                sprite: Sprite { color: Color::BLACK, custom_size: Some(Vec2::splat(BAR_SIZE)), ..default() },
+                transform: Transform::from_translation(at.extend(500.)),
+                ..default()
+            }
+        ));
+    }
+}
+
+// the play area grows in whole multiples to keep the pixel art crisp,
+// only a window smaller than the play area gets scaled down smoothly
+fn fit_view(
+    windows: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut projections: Query<&mut OrthographicProjection>,
+    mut ui_scale: ResMut<UiScale>,
+    mut current: Local<f32>,
+) {
+    let Ok(w) = windows.get_single() else { return };
+    let fit = (w.physical_width() as f32 / VIEW.x).min(w.physical_height() as f32 / VIEW.y);
+    // minimized
+    if fit <= 0. { return; }
+
+    let scale = if fit >= 1. { fit.floor() } else { fit };
+    // physical pixels per world unit, the projection counts in logical ones
+    let per_unit = scale / w.scale_factor() as f32;
+    if per_unit == *current { return; }
+    *current = per_unit;
+
+    for mut projection in projections.iter_mut() {
+        projection.scaling_mode = ScalingMode::WindowSize(per_unit);
+    }
+    // the menus and hud grow along with the play area
+    ui_scale.0 = per_unit as f64;
+}
+
+// overlays are laid out inside the play area rather than against the window edges, so they stay
+// clear of the letterbox. ui px are scaled like the world, so a node of VIEW px covers the play area
+pub fn play_area() -> (NodeBundle, NodeBundle) {
+    let screen = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    };
+    let area = NodeBundle {
+        style: Style {
+            width: Val::Px(VIEW.x),
+            height: Val::Px(VIEW.y),
+            flex_shrink: 0.,
+            ..default()
+        },
+        ..default()
+    };
+    (screen, area)
+}
+
+fn toggle_fullscreen(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keys.just_pressed(FULLSCREEN_KEY) {
+        settings.fullscreen = !settings.fullscreen;
+        settings.save();
+    }
+}
+
+fn apply_fullscreen(settings: Res<Settings>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    let Ok(mut w) = windows.get_single_mut() else { return };
+    let mode = if settings.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed };
+    if w.mode != mode {
+        w.mode = mode;
+    }
+}